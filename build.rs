@@ -20,13 +20,17 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut data: serde_json::Value = from_reader(read_to_string("distros.yaml")?.as_bytes())?;
 
+    let special_chars = regex::Regex::new(r"[\s_\-./!@]").unwrap();
+    let color_tags = regex::Regex::new(r"\{[0-9]+\}").unwrap();
+
     // for each distro
     for (name, obj) in data.as_object_mut().unwrap().iter_mut() {
         let obj = obj.as_object_mut().unwrap();
 
         // add rust type name
-        let reg = regex::Regex::new(r"[\s_\-./!@]").unwrap();
-        let type_name = reg.replace_all(name.as_str(), "").to_ascii_uppercase();
+        let type_name = special_chars
+            .replace_all(name.as_str(), "")
+            .to_ascii_uppercase();
         obj.insert("type_name".to_string(), json!(type_name));
 
         // Set default color if missing
@@ -36,8 +40,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         // Stripped & width
         let template = obj.get("ascii").unwrap().as_str().unwrap().to_string();
-        let reg = regex::Regex::new(r"\{[0-9]+\}").unwrap();
-        let stripped_ascii = reg.replace_all(&template, "");
+        let stripped_ascii = color_tags.replace_all(&template, "");
         let stripped_width = stripped_ascii
             .lines()
             .map(|l| l.chars().count())
@@ -48,8 +51,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         // add regex pattern (not provided: strip spaces + special chars from name)
         if obj.get("regex").is_none() {
-            let reg = regex::Regex::new(r"([\s_\-./!@])").unwrap();
-            let regex = reg
+            let regex = special_chars
                 .replace_all(name.as_str(), r"")
                 .to_string()
                 .to_ascii_lowercase()
@@ -5,7 +5,7 @@ use owo_colors::{
 };
 
 // Recommended to use `default-features=false` to avoid binary deps
-use punfetch::info::{PercentBar, DEFAULT_BAR_WIDTH};
+use punfetch::info::{BarStyle, Legend, PercentBar, DEFAULT_BAR_WIDTH};
 use punfetch::{
     // For the info module, add `features=["sysinfo"]`
     info::{sys, ColorBar, HostInfo},
@@ -66,6 +66,9 @@ fn main() {
             DynColors::Ansi(Red),
         ],
         width: DEFAULT_BAR_WIDTH,
+        style: BarStyle::Solid,
+        legend: Legend::default(),
+        thresholds: Vec::new(),
     });
 
     // Add our custom info
@@ -1,16 +1,26 @@
-use std::path::PathBuf;
+use std::{
+    io::{stdout, Write},
+    path::{Path, PathBuf},
+    process::exit,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use clap::{arg, command, Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use image::open;
 use owo_colors::{AnsiColors, DynColors};
-use sysinfo::SystemExt;
+use sysinfo::{System, SystemExt};
 use term_size::dimensions;
 
-use punfetch::{info::*, Distro, Printer};
+use punfetch::{
+    collector::DEFAULT_THREADS, info::*, Cache, Collector, Distro, Printer, Render, Timings,
+};
 
 #[derive(Parser)]
 #[command(author, version, about)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     /// Distribution to search art for (e.g. "Manjaro" or "Ubuntu")
     #[arg(long)]
     distro: Option<String>,
@@ -28,6 +38,109 @@ struct Args {
     /// Text color to use. Accepts ansi or hex color codes
     #[arg(long, value_name = "COLOR")]
     color: Option<String>,
+    /// Only show disks mounted on paths matching a glob (e.g. "/mnt/*"), may be repeated
+    #[arg(long, value_name = "GLOB", global = true)]
+    disk_include: Vec<String>,
+    /// Hide disks mounted on paths matching a glob, may be repeated
+    #[arg(long, value_name = "GLOB", global = true)]
+    disk_exclude: Vec<String>,
+    /// Show a line per disk with its filesystem and space used
+    #[arg(long)]
+    disk_details: bool,
+    /// Hardware lines to show, comma separated
+    #[arg(
+        long,
+        value_name = "LINES",
+        value_delimiter = ',',
+        default_value = "host"
+    )]
+    #[clap(value_enum)]
+    hardware: Vec<HardwareLine>,
+    /// Show the kernel command line under the boot line
+    #[arg(long)]
+    kernel_cmdline: bool,
+    /// Show cpu cache sizes and notable flags
+    #[arg(long)]
+    cpu_details: bool,
+    /// Sample cpu usage over an interval, adding it to the time budget of the cpu usage module
+    #[arg(long, value_name = "INTERVAL", num_args = 0..=1, default_missing_value = "200ms", value_parser = humantime::parse_duration)]
+    cpu_usage: Option<Duration>,
+    /// How to show batteries, if there are any
+    #[arg(long, value_name = "STYLE", default_value = "text")]
+    #[clap(value_enum)]
+    battery: BatteryStyle,
+    /// How to show connected displays
+    #[arg(long, value_name = "STYLE", default_value = "compact")]
+    #[clap(value_enum)]
+    display: DisplayStyle,
+    /// How to show the security modules, lockdown and kernel taint
+    #[arg(long, value_name = "STYLE", default_value = "compact")]
+    #[clap(value_enum)]
+    security: SecurityStyle,
+    /// How to show network interfaces
+    #[arg(long, value_name = "STYLE", default_value = "compact")]
+    #[clap(value_enum)]
+    network: NetworkStyle,
+    /// Only show network interfaces matching a glob (e.g. "wl*"), may be repeated
+    #[arg(long, value_name = "GLOB")]
+    network_include: Vec<String>,
+    /// Hide network interfaces matching a glob, on top of container and vm bridges
+    #[arg(long, value_name = "GLOB")]
+    network_exclude: Vec<String>,
+    /// Show mac addresses of network interfaces
+    #[arg(long)]
+    network_mac: bool,
+    /// Unit of the cpu, gpu and nvme temperatures
+    #[arg(long, value_name = "UNIT", default_value = "celsius")]
+    #[clap(value_enum)]
+    temp_unit: TempUnit,
+    /// Style of the usage bars
+    #[arg(long, value_name = "STYLE", default_value = "solid")]
+    #[clap(value_enum)]
+    bar_style: BarStyle,
+    /// Maximum number of legend entries on one row under the bars
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_LEGEND_PER_ROW)]
+    legend_per_row: usize,
+    /// How to show memory usage
+    #[arg(long, value_name = "STYLE", default_value = "text")]
+    #[clap(value_enum)]
+    memory: MemoryStyle,
+    /// How to show the 1, 5 and 15 minute load averages
+    #[arg(long, value_name = "FORMAT", default_value = "raw")]
+    #[clap(value_enum)]
+    load_format: LoadFormat,
+    /// Warn and critical disk usage, in percent
+    #[arg(long, value_name = "WARN,CRIT", default_value = "90,95", global = true)]
+    disk_thresholds: Thresholds,
+    /// Warn and critical memory usage, in percent
+    #[arg(long, value_name = "WARN,CRIT", default_value = "80,90", global = true)]
+    memory_thresholds: Thresholds,
+    /// Warn and critical swap usage, in percent
+    #[arg(long, value_name = "WARN,CRIT", default_value = "50,80", global = true)]
+    swap_thresholds: Thresholds,
+    /// Warn and critical load average, in percent of the core count
+    #[arg(
+        long,
+        value_name = "WARN,CRIT",
+        default_value = "70,100",
+        global = true
+    )]
+    load_thresholds: Thresholds,
+    /// Number of threads used to collect info
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_THREADS)]
+    threads: usize,
+    /// Time budget for each module, slower modules are left out (e.g. "250ms")
+    #[arg(long, value_name = "DURATION", default_value = "500ms", value_parser = humantime::parse_duration)]
+    timeout: Duration,
+    /// Print how long each step took to stderr
+    #[arg(long)]
+    timings: bool,
+    /// Don't read or write cached modules
+    #[arg(long)]
+    no_cache: bool,
+    /// Ignore cached modules and cache them again
+    #[arg(long, conflicts_with = "no_cache")]
+    refresh_cache: bool,
 }
 
 #[derive(Default, Clone, ValueEnum)]
@@ -48,6 +161,23 @@ impl ShowLogo {
     }
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Check disk, memory, swap and load usage against the thresholds
+    ///
+    /// Prints a line per failed check, and exits with 0, 1 or 2 for ok, warning or critical.
+    Check,
+}
+
+#[derive(Default, Clone, ValueEnum, PartialEq, Eq)]
+enum MemoryStyle {
+    /// A single line in the system info
+    #[default]
+    Text,
+    /// Stacked bars for memory and swap
+    Bar,
+}
+
 #[derive(Default, Debug, Clone, ValueEnum, PartialEq, Eq)]
 pub enum ColorMode {
     #[default]
@@ -66,13 +196,113 @@ impl ColorMode {
     }
 }
 
+/// Add a module to the collector, through the cache if enabled
+fn with_module<M: Module>(
+    collector: &mut Collector<System>,
+    cache: &Option<Arc<Cache>>,
+    color: DynColors,
+) {
+    match cache {
+        Some(cache) => collector.with_cached::<M>(cache, color),
+        None => collector.with::<M>(),
+    }
+}
+
+/// Add a module built by a custom constructor to the collector, through the cache if enabled.
+/// The `options` it is configured with are part of the cache hint.
+fn with_configured<M, F>(
+    collector: &mut Collector<System>,
+    cache: &Option<Arc<Cache>>,
+    color: DynColors,
+    options: String,
+    constructor: F,
+) where
+    M: Module,
+    F: FnOnce(&System) -> M + Send + 'static,
+{
+    match cache {
+        Some(cache) => collector.with_cached_constructor(cache, color, &options, constructor),
+        None => collector.with_constructor(constructor),
+    }
+}
+
+/// Run every check, returning the exit code of the worst one
+fn check(
+    disk_options: &DiskOptions,
+    memory: Thresholds,
+    swap: Thresholds,
+    load: Thresholds,
+) -> i32 {
+    let sys = System::new_with_specifics(merge_refresh_kinds(
+        DiskInfo::refresh_kind(),
+        SystemInfo::refresh_kind(),
+    ));
+    let memory = MemoryInfo {
+        thresholds: memory,
+        swap_thresholds: swap,
+        ..MemoryInfo::from_system(&sys)
+    };
+
+    let mut checks = DiskInfo::with_options(&sys, disk_options).checks();
+    checks.extend(memory.checks());
+    checks.push(load_check(&sys, load));
+
+    let failed: Vec<_> = checks
+        .iter()
+        .filter(|c| c.status() != Status::Normal)
+        .collect();
+    for check in &failed {
+        println!("{check}");
+    }
+    if failed.is_empty() {
+        println!("OK {} checks passed", checks.len());
+    }
+
+    checks
+        .iter()
+        .map(Check::status)
+        .max()
+        .unwrap_or_default()
+        .code()
+}
+
 fn main() {
+    let start = Instant::now();
     let args = Args::parse();
+    let mut disk_options = DiskOptions {
+        include: args.disk_include,
+        exclude: args.disk_exclude,
+        details: args.disk_details,
+        style: args.bar_style,
+        thresholds: args.disk_thresholds,
+        ..Default::default()
+    };
+    if let Some(Command::Check) = args.command {
+        exit(check(
+            &disk_options,
+            args.memory_thresholds,
+            args.swap_thresholds,
+            args.load_thresholds,
+        ));
+    }
+
     let mut printer = Printer::default();
+    let mut timings = Timings::default();
+
+    let cache = (!args.no_cache)
+        .then(Cache::new)
+        .flatten()
+        .map(|mut cache| {
+            cache.refresh = args.refresh_cache;
+            Arc::new(cache)
+        });
 
-    let sys = sys();
-    let host_info = HostInfo::new(&sys);
-    let distro = Distro::search(host_info.distro.clone());
+    // only static info is used, so an empty system is enough
+    let host_info = HostInfo::new(&System::new());
+    let distro = timings.time("distro search", || match &cache {
+        Some(cache) => Distro::search_cached(host_info.distro, cache),
+        None => Distro::search(host_info.distro),
+    });
 
     let colors = args.color_mode.mode();
     printer.with_color(
@@ -83,7 +313,7 @@ fn main() {
 
     if args.show_logo.should_show() {
         if let Some(path) = args.image {
-            match open(path) {
+            match timings.time("image decode", || open(path)) {
                 Ok(image) => printer.with_image(image),
                 Err(e) => eprintln!("Error opening image: {e}"),
             }
@@ -92,11 +322,148 @@ fn main() {
         }
     }
 
-    printer.with_info(UserInfo::new(&sys));
-    printer.with_info(host_info);
-    printer.with_info(PercentBar::from(sys.disks()));
-    printer.with_info(SystemInfo::new(&sys));
-    printer.with_info(ColorBar::default());
+    // legends wrap to the columns the terminal leaves next to the logo
+    let legend = Legend {
+        per_row: args.legend_per_row,
+        width: dimensions()
+            .zip(printer.logo_width())
+            .map(|((columns, _), logo)| columns.saturating_sub(logo))
+            .unwrap_or(DEFAULT_LEGEND_WIDTH),
+    };
+    disk_options.legend = legend;
 
-    printer.render()
+    let mut collector = Collector::default();
+    collector.threads = args.threads;
+    collector.timeout = args.timeout;
+    with_module::<UserInfo>(&mut collector, &cache, printer.color);
+    with_module::<HostInfo>(&mut collector, &cache, printer.color);
+    let hardware = args.hardware;
+    with_configured(
+        &mut collector,
+        &cache,
+        printer.color,
+        format!("{hardware:?}"),
+        move |sys| HardwareInfo::from_system(sys).only(&hardware),
+    );
+    collector.with::<VirtualizationInfo>();
+    let show_cmdline = args.kernel_cmdline;
+    collector.with_constructor(move |sys| BootInfo {
+        show_cmdline,
+        ..BootInfo::from_system(sys)
+    });
+    let security = args.security;
+    collector.with_constructor(move |sys| SecurityInfo {
+        style: security,
+        ..SecurityInfo::from_system(sys)
+    });
+    collector.with_constructor(move |sys| DiskInfo::with_options(sys, &disk_options));
+    let system_options = SystemOptions {
+        load_format: args.load_format,
+        memory_thresholds: args.memory_thresholds,
+        load_thresholds: args.load_thresholds,
+    };
+    let (cpu_details, cached) = (args.cpu_details, cache.is_some());
+    with_configured(
+        &mut collector,
+        &cache,
+        printer.color,
+        format!("{cpu_details}"),
+        move |sys| {
+            let cpu = CpuInfo::from_system(sys);
+            CpuInfo {
+                details: cpu_details,
+                // the current frequency would go stale in the cache, so only the maximum is shown
+                current_mhz: cpu.current_mhz.filter(|_| !cached || cpu.max_mhz.is_none()),
+                ..cpu
+            }
+        },
+    );
+    collector.with::<CgroupLimits>();
+    let display = args.display;
+    collector.with_constructor(move |sys| DisplayInfo {
+        style: display,
+        ..DisplayInfo::from_system(sys)
+    });
+    collector.with::<ThemeInfo>();
+    collector.with::<TerminalFontInfo>();
+    collector.with::<LocaleInfo>();
+    let unit = args.temp_unit;
+    collector.with_constructor(move |sys| SensorsInfo {
+        unit,
+        ..SensorsInfo::from_system(sys)
+    });
+    let mut network_options = NetworkOptions {
+        style: args.network,
+        include: args.network_include,
+        mac: args.network_mac,
+        ..Default::default()
+    };
+    network_options.exclude.extend(args.network_exclude);
+    collector.with_module(NetworkInfo::ID, move |_| {
+        NetworkInfo::with_options(&network_options)
+    });
+    let (battery, bar_style) = (args.battery, args.bar_style);
+    collector.with_constructor(move |sys| BatteryInfo {
+        style: battery,
+        bar_style,
+        ..BatteryInfo::from_system(sys)
+    });
+    if let Some(interval) = args.cpu_usage {
+        // sampling runs next to the other modules, its budget grows so it isn't cut short
+        let timeout = collector.timeout + interval;
+        collector.with_module_timeout(CpuUsage::ID, timeout, move |_| {
+            CpuUsage::sample(Path::new("/"), interval).unwrap_or_default()
+        });
+    }
+    if args.memory == MemoryStyle::Bar {
+        let style = args.bar_style;
+        collector.with_constructor(move |sys| MemoryInfo {
+            style,
+            legend,
+            thresholds: args.memory_thresholds,
+            swap_thresholds: args.swap_thresholds,
+            ..MemoryInfo::from_system(sys)
+        });
+        collector.with_constructor(move |sys| SystemInfo {
+            // the cpu module replaces the brand line of the system info
+            cpu: None,
+            memory: None,
+            ..SystemInfo::with_options(sys, &system_options)
+        });
+    } else {
+        collector.with_constructor(move |sys| SystemInfo {
+            // the cpu module replaces the brand line of the system info
+            cpu: None,
+            ..SystemInfo::with_options(sys, &system_options)
+        });
+    }
+    with_module::<ColorBar>(&mut collector, &cache, printer.color);
+
+    let sys = timings.time("sys refresh", || {
+        System::new_with_specifics(collector.refresh_kind())
+    });
+
+    let collected = timings.time("collect", || collector.collect(sys));
+    for module in collected {
+        timings.push(format!("{} (construct)", module.id), module.elapsed);
+        if let Some(info) = module.info {
+            let color = printer.color;
+            let lines = timings.time(format!("{} (render)", module.id), || info.render(color));
+            printer.with_info(lines);
+        }
+    }
+
+    let output = timings.time("layout", || printer.render_to_string());
+    timings.time("write", || {
+        let mut stdout = stdout().lock();
+        stdout
+            .write_all(output.as_bytes())
+            .and_then(|_| stdout.flush())
+            .expect("failed to write to stdout")
+    });
+
+    if args.timings {
+        timings.push("total", start.elapsed());
+        eprint!("{timings}");
+    }
 }
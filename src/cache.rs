@@ -0,0 +1,114 @@
+use std::{
+    env,
+    fs::{create_dir_all, read_to_string, rename, write},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// On-disk cache for slow modules, keyed by module id.
+///
+/// Each entry stores the time it was written and an invalidation hint alongside the cached lines.
+/// Entries are only returned while younger than the requested ttl and written with the same hint.
+pub struct Cache {
+    pub dir: PathBuf,
+    /// Ignore existing entries, while still writing new ones
+    pub refresh: bool,
+}
+
+impl Cache {
+    /// Cache stored in `$XDG_CACHE_HOME/punfetch`, or `~/.cache/punfetch` as a fallback
+    pub fn new() -> Option<Self> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+
+        Some(Self {
+            dir: base.join("punfetch"),
+            refresh: false,
+        })
+    }
+
+    /// Load an entry if it is younger than `ttl` and was stored with the same hint
+    pub fn load(&self, id: &str, ttl: Duration, hint: &str) -> Option<Vec<String>> {
+        if self.refresh {
+            return None;
+        }
+
+        let content = read_to_string(self.dir.join(id)).ok()?;
+        let mut lines = content.lines();
+        let written = Duration::from_secs(lines.next()?.parse().ok()?);
+        if lines.next()? != Self::hint(hint) || now().checked_sub(written)? >= ttl {
+            return None;
+        }
+
+        Some(lines.map(ToString::to_string).collect())
+    }
+
+    /// Store an entry, replacing any previous one
+    pub fn store(&self, id: &str, hint: &str, lines: &[String]) -> io::Result<()> {
+        create_dir_all(&self.dir)?;
+        let mut content = format!("{}\n{}\n", now().as_secs(), Self::hint(hint));
+        for line in lines {
+            content.push_str(line);
+            content.push('\n');
+        }
+
+        // write to a temporary file first, so concurrent runs never read a partial entry
+        let tmp = self.dir.join(format!(".{id}.{}", std::process::id()));
+        write(&tmp, content)?;
+        rename(tmp, self.dir.join(id))
+    }
+
+    /// Hints are versioned, and must fit on a single line
+    fn hint(hint: &str) -> String {
+        format!("{} {}", env!("CARGO_PKG_VERSION"), hint.replace('\n', " "))
+    }
+}
+
+/// Id of the running boot as a cache hint, for hardware details that only change across reboots
+pub fn boot_hint() -> String {
+    read_to_string("/proc/sys/kernel/random/boot_id")
+        .map(|id| id.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::remove_dir_all;
+
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn cache(name: &str) -> Cache {
+        Cache {
+            dir: env::temp_dir().join(format!("punfetch-cache-{name}-{}", std::process::id())),
+            refresh: false,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut cache = cache("roundtrip");
+        let lines = vec!["Cpu: fast".to_string(), String::new()];
+        cache.store("cpu", "6.1.0\nx86", &lines).unwrap();
+
+        assert_eq!(cache.load("cpu", HOUR, "6.1.0\nx86"), Some(lines));
+        assert_eq!(cache.load("cpu", HOUR, "6.2.0"), None);
+        assert_eq!(cache.load("cpu", Duration::ZERO, "6.1.0\nx86"), None);
+        assert_eq!(cache.load("gpu", HOUR, "6.1.0\nx86"), None);
+
+        cache.refresh = true;
+        assert_eq!(cache.load("cpu", HOUR, "6.1.0\nx86"), None);
+
+        remove_dir_all(cache.dir).unwrap();
+    }
+}
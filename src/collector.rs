@@ -0,0 +1,426 @@
+use std::{
+    collections::VecDeque,
+    panic::{catch_unwind, set_hook, take_hook, AssertUnwindSafe},
+    sync::{
+        mpsc::{channel, RecvTimeoutError, Sender},
+        Arc, Mutex, Once,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::{RefreshKind, System};
+
+#[cfg(feature = "sysinfo")]
+use crate::info::{merge_refresh_kinds, Module};
+
+use super::*;
+
+pub const DEFAULT_THREADS: usize = 4;
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Name of the worker threads, whose panics are reported as missing modules instead of printed
+const WORKER: &str = "punfetch-collector";
+
+/// Boxed [`Render`]-able info produced by a module
+pub type Info = Box<dyn Render + Send>;
+
+type Constructor<C> = Box<dyn FnOnce(&C) -> Info + Send>;
+type Queue<C> = Mutex<VecDeque<(usize, Constructor<C>)>>;
+
+enum Event {
+    Started(usize, Instant),
+    Finished(usize, Option<Info>, Duration),
+}
+
+/// Module added to a [`Collector`]
+struct Entry<C> {
+    id: &'static str,
+    constructor: Constructor<C>,
+    /// Time budget replacing [`Collector::timeout`]
+    timeout: Option<Duration>,
+    store: Option<Store>,
+}
+
+/// Where the rendered lines of a module are cached once it is collected
+struct Store {
+    cache: Arc<Cache>,
+    hint: String,
+    color: DynColors,
+}
+
+/// Result of a single module, returned in declared order
+pub struct Collected {
+    pub id: &'static str,
+    /// `None` if the module timed out or panicked
+    pub info: Option<Info>,
+    pub elapsed: Duration,
+}
+
+/// Runs module constructors concurrently on a small thread pool, sharing a context between them.
+///
+/// Each module gets [`Collector::timeout`] from the moment it starts; modules exceeding it are
+/// reported as missing instead of stalling the fetch.
+pub struct Collector<C> {
+    pub threads: usize,
+    pub timeout: Duration,
+    modules: Vec<Entry<C>>,
+    #[cfg(feature = "sysinfo")]
+    refresh_kind: RefreshKind,
+}
+
+impl<C> Default for Collector<C> {
+    fn default() -> Self {
+        Self {
+            threads: DEFAULT_THREADS,
+            timeout: DEFAULT_TIMEOUT,
+            modules: Vec::new(),
+            #[cfg(feature = "sysinfo")]
+            refresh_kind: RefreshKind::new(),
+        }
+    }
+}
+
+impl<C: Send + Sync + 'static> Collector<C> {
+    /// Add a module constructor, output order follows the order modules are added in
+    #[inline]
+    pub fn with_module<R, F>(&mut self, id: &'static str, constructor: F)
+    where
+        R: Render + Send + 'static,
+        F: FnOnce(&C) -> R + Send + 'static,
+    {
+        self.modules.push(Entry {
+            id,
+            constructor: Box::new(move |ctx| Box::new(constructor(ctx))),
+            timeout: None,
+            store: None,
+        });
+    }
+
+    /// Add a module constructor with its own time budget instead of [`Collector::timeout`], e.g.
+    /// for a module sampling over an interval
+    pub fn with_module_timeout<R, F>(&mut self, id: &'static str, timeout: Duration, constructor: F)
+    where
+        R: Render + Send + 'static,
+        F: FnOnce(&C) -> R + Send + 'static,
+    {
+        self.with_module(id, constructor);
+        if let Some(entry) = self.modules.last_mut() {
+            entry.timeout = Some(timeout);
+        }
+    }
+
+    /// Ids of the added modules, in order
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.modules.iter().map(|entry| entry.id)
+    }
+
+    /// Run all modules and wait for them to finish or time out.
+    ///
+    /// Modules added through the cache are rendered once collected, and their lines stored.
+    pub fn collect(self, ctx: C) -> Vec<Collected> {
+        let mut ids = Vec::new();
+        let mut constructors = Vec::new();
+        let mut timeouts = Vec::new();
+        let mut stores = Vec::new();
+        for entry in self.modules {
+            ids.push(entry.id);
+            constructors.push(entry.constructor);
+            timeouts.push(entry.timeout.unwrap_or(self.timeout));
+            stores.push(entry.store);
+        }
+        let mut pending = ids.len();
+        let mut results: Vec<Option<Collected>> = ids.iter().map(|_| None).collect();
+        let mut started: Vec<Option<Instant>> = vec![None; ids.len()];
+
+        let ctx = Arc::new(ctx);
+        let queue: Arc<Queue<C>> =
+            Arc::new(Mutex::new(constructors.into_iter().enumerate().collect()));
+        let (tx, rx) = channel();
+        silence_workers();
+        let spawn = || {
+            let (ctx, queue, tx) = (ctx.clone(), queue.clone(), tx.clone());
+            thread::Builder::new()
+                .name(WORKER.to_string())
+                .spawn(move || work::<C>(&ctx, &queue, &tx))
+                .expect("failed to spawn collector thread");
+        };
+        for _ in 0..self.threads.clamp(1, pending.max(1)) {
+            spawn();
+        }
+
+        while pending > 0 {
+            let deadline = (0..ids.len())
+                .filter_map(|i| match (started[i], &results[i]) {
+                    (Some(start), None) => Some(start + timeouts[i]),
+                    _ => None,
+                })
+                .min();
+            let event = match deadline {
+                Some(deadline) => {
+                    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match event {
+                Ok(Event::Started(i, at)) => started[i] = Some(at),
+                Ok(Event::Finished(i, info, elapsed)) => {
+                    if results[i].is_none() {
+                        results[i] = Some(Collected {
+                            id: ids[i],
+                            info,
+                            elapsed,
+                        });
+                        pending -= 1;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    for i in 0..ids.len() {
+                        match (started[i], &results[i]) {
+                            (Some(start), None) if now >= start + timeouts[i] => {
+                                results[i] = Some(Collected {
+                                    id: ids[i],
+                                    info: None,
+                                    elapsed: now - start,
+                                });
+                                pending -= 1;
+                                // the stuck worker is abandoned, replace it to keep the pool size
+                                spawn();
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        results
+            .into_iter()
+            .zip(stores)
+            .filter_map(|(collected, store)| Some((collected?, store)))
+            .map(|(mut collected, store)| {
+                if let (Some(info), Some(store)) = (&collected.info, store) {
+                    let lines = info.render(store.color);
+                    // failing to cache is not fatal, the module is just recomputed next time
+                    let _ = store.cache.store(collected.id, &store.hint, &lines);
+                    collected.info = Some(Box::new(lines));
+                }
+                collected
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Collector<System> {
+    /// Add a [`Module`] built with [`Module::from_system`]
+    #[inline]
+    pub fn with<M: Module>(&mut self) {
+        self.with_constructor(M::from_system);
+    }
+
+    /// Add a [`Module`] built by a custom constructor, e.g. to pass it some configuration
+    #[inline]
+    pub fn with_constructor<M, F>(&mut self, constructor: F)
+    where
+        M: Module,
+        F: FnOnce(&System) -> M + Send + 'static,
+    {
+        self.refresh_kind = merge_refresh_kinds(self.refresh_kind, M::refresh_kind());
+        self.with_module(M::ID, constructor);
+    }
+
+    /// Add a [`Module`], serving it from the cache while its entry is valid.
+    ///
+    /// Modules without a [`Module::TTL`] are always built. Fresh modules are cached once collected.
+    #[inline]
+    pub fn with_cached<M: Module>(&mut self, cache: &Arc<Cache>, color: DynColors) {
+        self.with_cached_constructor(cache, color, "", M::from_system);
+    }
+
+    /// Add a [`Module`] built by a custom constructor, serving it from the cache while its entry
+    /// is valid. The `options` the constructor is configured with are part of the cache hint.
+    pub fn with_cached_constructor<M, F>(
+        &mut self,
+        cache: &Arc<Cache>,
+        color: DynColors,
+        options: &str,
+        constructor: F,
+    ) where
+        M: Module,
+        F: FnOnce(&System) -> M + Send + 'static,
+    {
+        let Some(ttl) = M::TTL else {
+            return self.with_constructor(constructor);
+        };
+
+        let hint = format!("{color:?} {options} {}", M::cache_hint());
+        if let Some(lines) = cache.load(M::ID, ttl, &hint) {
+            self.with_module(M::ID, move |_| lines);
+        } else {
+            self.with_constructor(constructor);
+            if let Some(entry) = self.modules.last_mut() {
+                entry.store = Some(Store {
+                    cache: cache.clone(),
+                    hint,
+                    color,
+                });
+            }
+        }
+    }
+
+    /// Subsystems required by the added modules, to build the [`System`] passed to
+    /// [`Collector::collect`] with
+    pub fn refresh_kind(&self) -> RefreshKind {
+        self.refresh_kind
+    }
+}
+
+/// Keep the panic hook from printing panics of worker threads, once per process.
+///
+/// Panics on other threads still reach the previous hook.
+fn silence_workers() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = take_hook();
+        set_hook(Box::new(move |info| {
+            if thread::current().name() != Some(WORKER) {
+                previous(info);
+            }
+        }));
+    });
+}
+
+/// Worker loop, pulling constructors off the queue until it is empty
+fn work<C>(ctx: &C, queue: &Queue<C>, tx: &Sender<Event>) {
+    loop {
+        let next = queue.lock().ok().and_then(|mut q| q.pop_front());
+        let Some((i, constructor)) = next else {
+            return;
+        };
+
+        let start = Instant::now();
+        if tx.send(Event::Started(i, start)).is_err() {
+            return;
+        }
+        let info = catch_unwind(AssertUnwindSafe(|| constructor(ctx))).ok();
+        if tx.send(Event::Finished(i, info, start.elapsed())).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use owo_colors::AnsiColors;
+
+    use super::*;
+
+    fn lines(collected: &Collected) -> Option<Vec<String>> {
+        collected
+            .info
+            .as_ref()
+            .map(|i| i.render(DynColors::Ansi(AnsiColors::Default)))
+    }
+
+    #[test]
+    fn preserves_order() {
+        let mut collector = Collector::default();
+        for (i, delay) in [30, 0, 20, 10, 0].into_iter().enumerate() {
+            collector.with_module("module", move |base: &usize| {
+                sleep(Duration::from_millis(delay));
+                (base + i).to_string()
+            });
+        }
+
+        let results = collector.collect(10);
+        let lines: Vec<_> = results.iter().flat_map(|c| lines(c).unwrap()).collect();
+        assert_eq!(lines, ["10", "11", "12", "13", "14"]);
+    }
+
+    #[test]
+    fn timeout_is_missing() {
+        let mut collector = Collector {
+            threads: 1,
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        collector.with_module("fast", |_: &()| "fast".to_string());
+        collector.with_module("slow", |_| {
+            sleep(Duration::from_secs(5));
+            "slow".to_string()
+        });
+        collector.with_module("after", |_| "after".to_string());
+
+        let start = Instant::now();
+        let results = collector.collect(());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            results.iter().map(|c| c.id).collect::<Vec<_>>(),
+            ["fast", "slow", "after"]
+        );
+        assert!(results[1].info.is_none());
+        assert_eq!(lines(&results[2]), Some(vec!["after".to_string()]));
+    }
+
+    #[test]
+    fn stores_collected() {
+        let cache = Arc::new(Cache {
+            dir: std::env::temp_dir().join(format!("punfetch-collect-{}", std::process::id())),
+            refresh: false,
+        });
+        let mut collector = Collector::default();
+        collector.with_module("host", |_: &()| "Host: test".to_string());
+        collector.with_module("panics", |_| -> String { panic!("oops") });
+        for entry in &mut collector.modules {
+            entry.store = Some(Store {
+                cache: cache.clone(),
+                hint: "hint".to_string(),
+                color: DynColors::Ansi(AnsiColors::Default),
+            });
+        }
+
+        let results = collector.collect(());
+        let hour = Duration::from_secs(3600);
+        assert_eq!(lines(&results[0]), cache.load("host", hour, "hint"));
+        assert_eq!(cache.load("panics", hour, "hint"), None);
+
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn module_timeout() {
+        let mut collector = Collector {
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        for id in ["short", "long"] {
+            let timeout = Duration::from_millis(if id == "long" { 500 } else { 50 });
+            collector.with_module_timeout(id, timeout, |_: &()| {
+                sleep(Duration::from_millis(100));
+                "done".to_string()
+            });
+        }
+
+        let results = collector.collect(());
+        assert!(results[0].info.is_none());
+        assert!(results[1].info.is_some());
+    }
+
+    #[test]
+    fn panic_is_missing() {
+        let mut collector = Collector::default();
+        collector.with_module("panics", |_: &()| -> String { panic!("oops") });
+        collector.with_module("ok", |_| "ok".to_string());
+
+        let results = collector.collect(());
+        assert!(results[0].info.is_none());
+        assert!(results[1].info.is_some());
+    }
+}
@@ -1,9 +1,14 @@
 //! Supported distributions. Base enum is generated from the `distros.yaml` file
 
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
 use onefetch_ascii::AsciiArt;
 
+use crate::Cache;
+
+/// How long a distro match stays in the [`Cache`]
+const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 include!(concat!(env!("OUT_DIR"), "/distros.rs"));
 
 impl Distro {
@@ -19,13 +24,29 @@ impl Distro {
 
         // search for distro
         let m = Self::regex().matches(&str);
-        if let Some(m) = m.into_iter().last() {
+        if let Some(m) = m.into_iter().next_back() {
             (m as u64).into()
         } else {
             Distro::DEFAULT
         }
     }
 
+    /// Find a distro from a string, reusing the previous match for the same string from the cache
+    pub fn search_cached<S: Display>(str: S, cache: &Cache) -> Self {
+        let name = str.to_string();
+        let cached = cache
+            .load("distro", CACHE_TTL, &name)
+            .and_then(|lines| lines.first()?.parse::<u64>().ok());
+        if let Some(distro) = cached {
+            return distro.into();
+        }
+
+        let distro = Self::search(&name);
+        // failing to cache is not fatal, the distro is just searched again next time
+        let _ = cache.store("distro", &name, &[(distro as u64).to_string()]);
+        distro
+    }
+
     /// Build ascii art from the inner template and colors
     pub fn ascii<'a>(&self, colors: Option<bool>) -> AsciiArt<'a> {
         let colors = self.colors(colors);
@@ -67,6 +88,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn search_cached() {
+        let cache = crate::Cache {
+            dir: std::env::temp_dir().join(format!("punfetch-distro-{}", std::process::id())),
+            refresh: false,
+        };
+
+        assert_eq!(Distro::search_cached("Arch Linux", &cache), Distro::ARCH);
+        assert_eq!(
+            cache.load("distro", super::CACHE_TTL, "Arch Linux"),
+            Some(vec![(Distro::ARCH as u64).to_string()])
+        );
+
+        // a planted entry proves the second search is served from the cache
+        let planted = [(Distro::FEDORA as u64).to_string()];
+        cache.store("distro", "Arch Linux", &planted).unwrap();
+        assert_eq!(Distro::search_cached("Arch Linux", &cache), Distro::FEDORA);
+        assert_eq!(Distro::search_cached("Fedora 33", &cache), Distro::FEDORA);
+
+        std::fs::remove_dir_all(cache.dir).unwrap();
+    }
+
     #[test]
     fn ascii() {
         let distro = Distro::search("Arch Linux");
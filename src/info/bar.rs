@@ -0,0 +1,375 @@
+use owo_colors::{colored::Color, DynColors, OwoColorize};
+
+use super::*;
+
+/// Left aligned blocks, from one to seven eighths of a cell
+const LEFT_EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const SHADES: [char; 3] = ['█', '▓', '▒'];
+
+/// Default maximum number of legend entries on one row
+pub const DEFAULT_LEGEND_PER_ROW: usize = 2;
+/// Maximum visible width of a legend row when the terminal width is unknown, including the padding
+/// under the title
+pub const DEFAULT_LEGEND_WIDTH: usize = 72;
+
+/// Visual style of a [`PercentBar`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum BarStyle {
+    /// Colored background cells, using eighth blocks for fractional cells
+    #[default]
+    Solid,
+    /// Shade blocks cycling `█▓▒` for each item, with `░` for the remainder
+    Shade,
+    /// Plain ascii, like `[###---]`
+    Ascii,
+    /// Colored dots, with `·` for the remainder
+    Dots,
+}
+
+/// Layout of the legend under a [`PercentBar`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Legend {
+    /// Maximum number of entries on one row
+    pub per_row: usize,
+    /// Maximum visible width of a row, entries wider than this get a row of their own
+    pub width: usize,
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Self {
+            per_row: DEFAULT_LEGEND_PER_ROW,
+            width: DEFAULT_LEGEND_WIDTH,
+        }
+    }
+}
+
+/// Generic percentage bar. Requires a total, a vector of items, and some colors to cycle.
+pub struct PercentBar {
+    pub title: String,
+    pub total: f64,
+    pub items: Vec<(String, f64, f64)>,
+    pub colors: Vec<DynColors>,
+    pub width: usize,
+    pub style: BarStyle,
+    pub legend: Legend,
+    /// Thresholds of each item by index, recoloring items past them regardless of the palette
+    pub thresholds: Vec<Option<Thresholds>>,
+}
+
+/// A value as a non-negative finite number, anything else counts as zero
+fn finite(value: f64) -> f64 {
+    if value.is_finite() && value > 0.0 {
+        value
+    } else {
+        0.0
+    }
+}
+
+impl PercentBar {
+    /// Total to scale the items against, never less than their sum so they always fit
+    fn scale_total(&self) -> f64 {
+        let sum: f64 = self.items.iter().map(|(_, v, _)| finite(*v)).sum();
+        finite(self.total).max(sum)
+    }
+
+    /// Status of the item at the given index, from its percent and thresholds
+    pub fn status(&self, i: usize) -> Status {
+        match (self.thresholds.get(i), self.items.get(i)) {
+            (Some(Some(thresholds)), Some((_, _, percent))) => thresholds.status(*percent),
+            _ => Status::Normal,
+        }
+    }
+
+    /// Color of the item at the given index
+    fn item_color(&self, i: usize) -> DynColors {
+        if let Some(color) = self.status(i).color() {
+            color
+        } else if self.colors.is_empty() {
+            DynColors::Ansi(Color::Default)
+        } else {
+            self.colors[i % self.colors.len()]
+        }
+    }
+
+    /// Split `units` between the items, returning where each item ends.
+    ///
+    /// Every item with a positive value is given at least one unit while there is room left, so
+    /// small items never disappear entirely.
+    fn ends(&self, units: usize) -> Vec<usize> {
+        let total = self.scale_total();
+        let scale = if total > 0.0 {
+            units as f64 / total
+        } else {
+            0.0
+        };
+
+        let mut sum = 0.0;
+        let mut prev = 0;
+        let mut visible_after = self
+            .items
+            .iter()
+            .filter(|(_, v, _)| finite(*v) > 0.0)
+            .count();
+        self.items
+            .iter()
+            .map(|(_, value, _)| {
+                let value = finite(*value);
+                sum += value;
+                if value > 0.0 {
+                    visible_after -= 1;
+                }
+                // leave a unit for each of the following visible items
+                let max = units.saturating_sub(visible_after).max(prev);
+                let mut end = ((sum * scale).round() as usize).clamp(prev, max);
+                if end == prev && value > 0.0 && prev < max {
+                    end += 1;
+                }
+                prev = end;
+                end
+            })
+            .collect()
+    }
+
+    /// Render the bar itself, always exactly [`PercentBar::width`] cells wide
+    pub fn bar(&self) -> String {
+        let (width, scale) = match self.style {
+            BarStyle::Solid => (self.width, 8),
+            BarStyle::Ascii if self.width >= 2 => (self.width - 2, 1),
+            _ => (self.width, 1),
+        };
+        let ends = self.ends(width * scale);
+        let owner = |unit: usize| ends.iter().position(|&end| unit < end);
+        let color = |owner: Option<usize>| match owner {
+            Some(i) => self.item_color(i),
+            None => DynColors::Ansi(Color::Black),
+        };
+
+        let mut buf = String::new();
+        for cell in 0..width {
+            let start = cell * scale;
+            let (left, right) = (owner(start), owner(start + scale - 1));
+            let cell = match (self.style, left) {
+                (BarStyle::Solid, _) if left == right => " ".on_color(color(left)).to_string(),
+                (BarStyle::Solid, _) => {
+                    let eighths = (start..start + scale).filter(|&u| owner(u) == left).count();
+                    LEFT_EIGHTHS[eighths - 1]
+                        .color(color(left))
+                        .on_color(color(right))
+                        .to_string()
+                }
+                (BarStyle::Shade, Some(i)) => {
+                    SHADES[i % SHADES.len()].color(color(left)).to_string()
+                }
+                (BarStyle::Shade, None) => "░".to_string(),
+                (BarStyle::Ascii, Some(_)) => "#".color(color(left)).to_string(),
+                (BarStyle::Ascii, None) => "-".to_string(),
+                (BarStyle::Dots, Some(_)) => "●".color(color(left)).to_string(),
+                (BarStyle::Dots, None) => "·".to_string(),
+            };
+            buf.push_str(&cell);
+        }
+
+        if self.style == BarStyle::Ascii && self.width >= 2 {
+            format!("[{buf}]")
+        } else {
+            buf
+        }
+    }
+}
+
+impl Render for PercentBar {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let mut bufs = vec![format!(
+            "{}: {}",
+            self.title.bold().color(color),
+            self.bar()
+        )];
+        let padding = " ".repeat(self.title.len() + 1);
+        let mut row = (0, 0);
+        for (i, (name, _, percent)) in self.items.iter().enumerate() {
+            let percent = if percent.is_nan() {
+                0.0
+            } else {
+                percent.clamp(0.0, 1.0) * 100.0
+            };
+            let label = format!("{name} ({percent:.1} %)");
+            let width = label.chars().count() + 4;
+
+            // start a new row when this entry doesn't fit on the current one
+            let (len, row_width) = row;
+            if len > 0 && len < self.legend.per_row && row_width + width <= self.legend.width {
+                row = (len + 1, row_width + width);
+            } else {
+                bufs.push(padding.clone());
+                row = (1, padding.len() + width);
+            }
+
+            let buf = bufs.last_mut().expect("a legend row was pushed");
+            buf.push_str(&format!(" {} {label} ", "●".color(self.item_color(i))));
+        }
+
+        bufs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Visible width of a line, ignoring ansi escape sequences
+    fn visible_width(line: &str) -> usize {
+        let mut width = 0;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                width += 1;
+            }
+        }
+        width
+    }
+
+    fn bar(items: &[f64], total: f64, width: usize, style: BarStyle) -> PercentBar {
+        PercentBar {
+            title: "Test".to_string(),
+            total,
+            items: items
+                .iter()
+                .map(|&v| (String::new(), v, v / total))
+                .collect(),
+            colors: DEFAULT_COLORS.to_vec(),
+            width,
+            style,
+            legend: Legend::default(),
+            thresholds: Vec::new(),
+        }
+    }
+
+    fn styles() -> impl Strategy<Value = BarStyle> {
+        prop_oneof![
+            Just(BarStyle::Solid),
+            Just(BarStyle::Shade),
+            Just(BarStyle::Ascii),
+            Just(BarStyle::Dots),
+        ]
+    }
+
+    fn values() -> impl Strategy<Value = f64> {
+        prop_oneof![
+            4 => 0.0..1e6,
+            1 => any::<f64>(),
+            1 => Just(f64::NAN),
+            1 => Just(f64::INFINITY),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn exact_width(
+            items in prop::collection::vec(values(), 0..8),
+            total in values(),
+            width in 0..60usize,
+            style in styles(),
+        ) {
+            let bar = bar(&items, total, width, style);
+            prop_assert_eq!(visible_width(&bar.bar()), width);
+
+            let units = width * 8;
+            let ends = bar.ends(units);
+            prop_assert!(ends.windows(2).all(|w| w[0] <= w[1]));
+            prop_assert!(ends.iter().all(|&end| end <= units));
+        }
+
+        #[test]
+        fn legend_fits(
+            names in prop::collection::vec("[a-z/ ]{0,30}", 0..8),
+            per_row in 0..5usize,
+            legend_width in 0..100usize,
+        ) {
+            let bar = PercentBar {
+                items: names.iter().map(|n| (n.clone(), 1.0, 1.0)).collect(),
+                colors: Vec::new(),
+                legend: Legend {
+                    per_row,
+                    width: legend_width,
+                },
+                ..bar(&[], 1.0, 10, BarStyle::Solid)
+            };
+            let lines = bar.render(DynColors::Ansi(Color::Default));
+            prop_assert_eq!(lines.iter().map(|l| l.matches('●').count()).sum::<usize>(), names.len());
+            for line in &lines[1..] {
+                let entries = line.matches('●').count();
+                prop_assert!(entries <= per_row.max(1));
+                prop_assert!(entries == 1 || visible_width(line) <= legend_width);
+            }
+        }
+    }
+
+    #[test]
+    fn overflowing_items() {
+        // used values larger than the total, like a disk reporting more used than its size
+        let bar = bar(&[150.0, 100.0], 100.0, 10, BarStyle::Dots);
+        assert_eq!(bar.ends(10), [6, 10]);
+        let lines = bar.render(DynColors::Ansi(Color::Default));
+        assert!(lines[1].contains("(100.0 %)"));
+    }
+
+    #[test]
+    fn legend_wraps() {
+        let bar = PercentBar {
+            items: ["short", "short", "a much longer legend entry"]
+                .into_iter()
+                .map(|n| (n.to_string(), 1.0, 0.5))
+                .collect(),
+            legend: Legend {
+                per_row: 3,
+                width: 45,
+            },
+            ..bar(&[], 1.0, 10, BarStyle::Solid)
+        };
+        let lines = bar.render(DynColors::Ansi(Color::Default));
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].matches('●').count(), 2);
+    }
+
+    #[test]
+    fn thresholds() {
+        let bar = PercentBar {
+            thresholds: vec![Some(DISK_THRESHOLDS), None],
+            ..bar(&[95.0, 99.0], 100.0, 10, BarStyle::Dots)
+        };
+        assert_eq!(bar.status(0), Status::Critical);
+        // items without thresholds keep their palette color
+        assert_eq!(bar.status(1), Status::Normal);
+        assert_eq!(bar.item_color(0), DynColors::Ansi(Color::Red));
+        assert_eq!(bar.item_color(1), DEFAULT_COLORS[1]);
+    }
+
+    #[test]
+    fn small_items_are_visible() {
+        let bar = bar(&[1000.0, 1.0, 1.0], 1002.0, 10, BarStyle::Dots);
+        assert_eq!(bar.ends(10), [8, 9, 10]);
+    }
+
+    #[test]
+    fn eighths() {
+        let bar = bar(&[50.0, 25.0], 100.0, 3, BarStyle::Solid);
+        // 1.5 cells, then 0.75 cells, then 0.75 cells of remainder
+        assert_eq!(bar.ends(24), [12, 18]);
+        assert_eq!(visible_width(&bar.bar()), 3);
+        assert!(bar.bar().contains('▌') && bar.bar().contains('▎'));
+    }
+
+    #[test]
+    fn ascii() {
+        let bar = bar(&[50.0], 100.0, 12, BarStyle::Ascii);
+        let rendered = bar.bar();
+        assert!(rendered.starts_with('[') && rendered.ends_with("-----]"));
+        assert_eq!(visible_width(&rendered), 12);
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    fs::{read_dir, read_to_string},
+    path::Path,
+    time::Duration,
+};
+
+use humantime::format_duration;
+use owo_colors::{colored::Color, DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Battery drained past 80 % warns, past 90 % is critical
+pub const BATTERY_THRESHOLDS: Thresholds = Thresholds::new(0.8, 0.9);
+
+/// How batteries are shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum BatteryStyle {
+    /// A line per battery
+    #[default]
+    Text,
+    /// A gauge per battery
+    Bar,
+}
+
+/// A battery from `/sys/class/power_supply`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Battery {
+    pub name: String,
+    /// Charge, as a fraction
+    pub charge: f64,
+    /// State as reported by the kernel, e.g. `charging` or `full`
+    pub status: String,
+    /// Time until empty when discharging, or until full when charging
+    pub remaining: Option<Duration>,
+    /// Capacity left compared to the design capacity, as a fraction
+    pub health: Option<f64>,
+}
+
+/// Batteries and whether the ac adapter is plugged in
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BatteryInfo {
+    pub batteries: Vec<Battery>,
+    /// `None` when there is no ac adapter
+    pub ac: Option<bool>,
+    pub style: BatteryStyle,
+    pub bar_style: BarStyle,
+}
+
+impl BatteryInfo {
+    /// Read power supplies from `/sys/class/power_supply` under the given root
+    pub fn from_root(root: &Path) -> Self {
+        let mut supplies: Vec<_> = read_dir(root.join("sys/class/power_supply"))
+            .map(|dir| dir.filter_map(|e| Some(e.ok()?.path())).collect())
+            .unwrap_or_default();
+        supplies.sort();
+
+        let mut info = Self::default();
+        for supply in supplies {
+            let read = |file: &str| {
+                read_to_string(supply.join(file))
+                    .ok()
+                    .map(|s| s.trim().to_string())
+            };
+            match read("type").as_deref() {
+                Some("Battery") if read("scope").as_deref() != Some("Device") => {
+                    if let Some(battery) = battery(&supply) {
+                        info.batteries.push(battery);
+                    }
+                }
+                Some("Mains") => {
+                    let online = read("online").as_deref() == Some("1");
+                    info.ac = Some(info.ac.unwrap_or(false) || online);
+                }
+                _ => {}
+            }
+        }
+        info
+    }
+}
+
+/// Read a battery, using energy (µWh, µW) readings or charge (µAh, µA) ones when energy isn't
+/// reported. Units are never mixed, so a battery reporting energy but only a current gets its
+/// power from the current and voltage.
+fn battery(path: &Path) -> Option<Battery> {
+    let read = |file: &str| read_to_string(path.join(file)).ok();
+    let number = |file: &str| read(file)?.trim().parse::<f64>().ok();
+
+    let (now, full, design, rate) = if number("energy_now").is_some() {
+        // µA times µV is a millionth of a µW
+        let power = number("power_now")
+            .or_else(|| Some(number("current_now")? * number("voltage_now")? / 1e6));
+        let (full, design) = (number("energy_full"), number("energy_full_design"));
+        (number("energy_now"), full, design, power)
+    } else {
+        let (full, design) = (number("charge_full"), number("charge_full_design"));
+        (number("charge_now"), full, design, number("current_now"))
+    };
+    let rate = rate.map(f64::abs);
+
+    let charge = match (number("capacity"), now, full) {
+        (Some(capacity), _, _) => capacity / 100.0,
+        (None, Some(now), Some(full)) if full > 0.0 => now / full,
+        _ => return None,
+    };
+    let status = read("status")
+        .map(|s| s.trim().to_lowercase())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let hours = match (status.as_str(), now, full, rate) {
+        (_, _, _, Some(rate)) if rate <= 0.0 => None,
+        ("discharging", Some(now), _, Some(rate)) => Some(now / rate),
+        ("charging", Some(now), Some(full), Some(rate)) => Some((full - now).max(0.0) / rate),
+        _ => None,
+    };
+
+    Some(Battery {
+        name: path.file_name()?.to_string_lossy().to_string(),
+        charge: charge.clamp(0.0, 1.0),
+        status,
+        remaining: hours.map(|h| Duration::from_secs((h * 60.0).round() as u64 * 60)),
+        health: match (full, design) {
+            (Some(full), Some(design)) if design > 0.0 => Some(full / design),
+            _ => None,
+        },
+    })
+}
+
+impl Battery {
+    /// State, time remaining and health, e.g. `discharging, 2h 13m left, 91 % health`
+    fn details(&self) -> String {
+        let mut details = vec![self.status.clone()];
+        if let Some(remaining) = self.remaining {
+            let until = if self.status == "charging" {
+                "until full"
+            } else {
+                "left"
+            };
+            details.push(format!("{} {until}", format_duration(remaining)));
+        }
+        if let Some(health) = self.health {
+            details.push(format!("{:.0} % health", health * 100.0));
+        }
+        details.join(", ")
+    }
+}
+
+impl Render for BatteryInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let mut lines = Vec::new();
+        for battery in &self.batteries {
+            let title = if self.batteries.len() > 1 {
+                format!("Battery ({})", battery.name)
+            } else {
+                "Battery".to_string()
+            };
+            let mut details = battery.details();
+            if self.ac == Some(true) {
+                details.push_str(", ac connected");
+            }
+
+            let status = BATTERY_THRESHOLDS.status(1.0 - battery.charge);
+            match self.style {
+                BatteryStyle::Text => lines.push(format!(
+                    "{}: {} ({details})",
+                    title.bold().color(color),
+                    BATTERY_THRESHOLDS.paint(
+                        1.0 - battery.charge,
+                        format!("{:.0} %", battery.charge * 100.0)
+                    ),
+                )),
+                BatteryStyle::Bar => lines.extend(
+                    PercentBar {
+                        title,
+                        total: 1.0,
+                        items: vec![(details, battery.charge, battery.charge)],
+                        colors: vec![status.color().unwrap_or(DynColors::Ansi(Color::Green))],
+                        width: DEFAULT_BAR_WIDTH,
+                        style: self.bar_style,
+                        legend: Legend::default(),
+                        thresholds: Vec::new(),
+                    }
+                    .render(color),
+                ),
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for BatteryInfo {
+    const ID: &'static str = "battery";
+
+    fn from_system(_: &System) -> Self {
+        Self::from_root(Path::new("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    fn render(info: &BatteryInfo) -> Vec<String> {
+        info.render(DynColors::Ansi(AnsiColors::Default))
+    }
+
+    #[test]
+    fn discharging_laptop() {
+        let root = Fixture::new(&[
+            ("sys/class/power_supply/AC/type", "Mains\n"),
+            ("sys/class/power_supply/AC/online", "0\n"),
+            ("sys/class/power_supply/BAT0/type", "Battery\n"),
+            ("sys/class/power_supply/BAT0/status", "Discharging\n"),
+            ("sys/class/power_supply/BAT0/capacity", "84\n"),
+            ("sys/class/power_supply/BAT0/energy_now", "42000000\n"),
+            ("sys/class/power_supply/BAT0/energy_full", "50000000\n"),
+            (
+                "sys/class/power_supply/BAT0/energy_full_design",
+                "55000000\n",
+            ),
+            ("sys/class/power_supply/BAT0/power_now", "12000000\n"),
+            // wireless mouse, not a system battery
+            ("sys/class/power_supply/hidpp_battery_0/type", "Battery\n"),
+            ("sys/class/power_supply/hidpp_battery_0/scope", "Device\n"),
+            ("sys/class/power_supply/hidpp_battery_0/capacity", "50\n"),
+        ]);
+
+        let info = BatteryInfo::from_root(&root);
+        assert_eq!(info.ac, Some(false));
+        assert_eq!(
+            info.batteries,
+            [Battery {
+                name: "BAT0".to_string(),
+                charge: 0.84,
+                status: "discharging".to_string(),
+                remaining: Some(Duration::from_secs(3 * 3600 + 30 * 60)),
+                health: Some(50.0 / 55.0),
+            }]
+        );
+        assert!(render(&info)[0].ends_with("(discharging, 3h 30m left, 91 % health)"));
+
+        let bar = BatteryInfo {
+            style: BatteryStyle::Bar,
+            ..info
+        };
+        assert_eq!(render(&bar).len(), 2);
+    }
+
+    #[test]
+    fn charging_from_charge_readings() {
+        let root = Fixture::new(&[
+            ("sys/class/power_supply/ADP1/type", "Mains\n"),
+            ("sys/class/power_supply/ADP1/online", "1\n"),
+            ("sys/class/power_supply/BAT1/type", "Battery\n"),
+            ("sys/class/power_supply/BAT1/status", "Charging\n"),
+            ("sys/class/power_supply/BAT1/charge_now", "2000000\n"),
+            ("sys/class/power_supply/BAT1/charge_full", "4000000\n"),
+            ("sys/class/power_supply/BAT1/current_now", "1000000\n"),
+        ]);
+
+        let info = BatteryInfo::from_root(&root);
+        assert_eq!(info.batteries[0].charge, 0.5);
+        assert_eq!(
+            info.batteries[0].remaining,
+            Some(Duration::from_secs(2 * 3600))
+        );
+        assert!(render(&info)[0].ends_with("(charging, 2h until full, ac connected)"));
+    }
+
+    #[test]
+    fn energy_with_current_only() {
+        let battery = |voltage: Option<&'static str>| {
+            let mut files = vec![
+                ("sys/class/power_supply/BAT0/type", "Battery\n"),
+                ("sys/class/power_supply/BAT0/status", "Discharging\n"),
+                ("sys/class/power_supply/BAT0/energy_now", "30000000\n"),
+                ("sys/class/power_supply/BAT0/energy_full", "60000000\n"),
+                ("sys/class/power_supply/BAT0/current_now", "2000000\n"),
+            ];
+            files.extend(voltage.map(|v| ("sys/class/power_supply/BAT0/voltage_now", v)));
+            BatteryInfo::from_root(&Fixture::new(&files)).batteries[0].remaining
+        };
+
+        // 30 Wh at 2 A and 15 V
+        assert_eq!(battery(Some("15000000\n")), Some(Duration::from_secs(3600)));
+        assert_eq!(battery(None), None);
+    }
+
+    #[test]
+    fn desktop_without_battery() {
+        let root = Fixture::new(&[
+            ("sys/class/power_supply/AC/type", "Mains\n"),
+            ("sys/class/power_supply/AC/online", "1\n"),
+        ]);
+        assert!(render(&BatteryInfo::from_root(&root)).is_empty());
+    }
+}
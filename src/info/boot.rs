@@ -0,0 +1,278 @@
+use std::{
+    fs::{read, read_link, read_to_string},
+    path::Path,
+};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Efi variables of the global vendor, like `SecureBoot`
+const EFI_GLOBAL: &str = "8be4df61-93ca-11d2-aa0d-00e098032b8c";
+/// Efi variables of the boot loader interface, set by systemd-boot and others
+const EFI_LOADER: &str = "4a67b082-0a4c-41cf-b6c7-440b29bb8c4f";
+
+/// Files left by boot loaders that don't report themselves. An ESP mounted on `/boot/efi` or
+/// `/efi` is usually only readable by root, so the files on it are missed otherwise, while
+/// systemd-boot reports itself through `LoaderInfo` anyway.
+const BOOTLOADER_FILES: [(&str, &str); 9] = [
+    ("boot/loader/loader.conf", "systemd-boot"),
+    ("boot/efi/loader/loader.conf", "systemd-boot"),
+    ("efi/loader/loader.conf", "systemd-boot"),
+    ("boot/grub/grub.cfg", "GRUB"),
+    ("boot/grub2/grub.cfg", "GRUB"),
+    ("boot/limine.conf", "Limine"),
+    ("boot/limine/limine.conf", "Limine"),
+    ("boot/refind_linux.conf", "rEFInd"),
+    ("boot/syslinux/syslinux.cfg", "Syslinux"),
+];
+
+/// How the firmware started the system
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootMode {
+    Uefi,
+    Bios,
+}
+
+/// Init system, boot mode, secure boot state, boot loader and kernel command line
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BootInfo {
+    pub init: Option<String>,
+    pub mode: Option<BootMode>,
+    /// `None` when the firmware doesn't report it
+    pub secure_boot: Option<bool>,
+    pub bootloader: Option<String>,
+    pub cmdline: Option<String>,
+    /// Show the kernel command line
+    pub show_cmdline: bool,
+}
+
+impl BootInfo {
+    /// Read `/proc`, `/sys/firmware` and `/boot` under the given root
+    pub fn from_root(root: &Path) -> Self {
+        let efi = root.join("sys/firmware/efi");
+        let mode = if efi.is_dir() {
+            Some(BootMode::Uefi)
+        } else {
+            // containers often hide the firmware entirely
+            root.join("sys/firmware").is_dir().then_some(BootMode::Bios)
+        };
+
+        let efivar = |name: &str, vendor: &str| {
+            // 4 bytes of attributes precede the value
+            let data = read(efi.join(format!("efivars/{name}-{vendor}"))).ok()?;
+            data.get(4..).map(<[u8]>::to_vec)
+        };
+        let secure_boot = efivar("SecureBoot", EFI_GLOBAL).and_then(|v| Some(v.first()? == &1));
+        let bootloader = efivar("LoaderInfo", EFI_LOADER)
+            .and_then(|v| utf16(&v))
+            .or_else(|| {
+                BOOTLOADER_FILES
+                    .iter()
+                    .find(|(file, _)| root.join(file).exists())
+                    .map(|(_, name)| name.to_string())
+            });
+
+        let cmdline = read_to_string(root.join("proc/cmdline"))
+            .ok()
+            .map(|cmdline| cmdline.trim().to_string())
+            .filter(|cmdline| !cmdline.is_empty());
+
+        Self {
+            init: init(root),
+            mode,
+            secure_boot,
+            bootloader,
+            cmdline,
+            show_cmdline: false,
+        }
+    }
+}
+
+/// Nul terminated utf-16 text of an efi variable
+fn utf16(data: &[u8]) -> Option<String> {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .take_while(|&u| u != 0)
+        .collect();
+    let text = String::from_utf16(&units).ok()?;
+    (!text.trim().is_empty()).then(|| text.trim().to_string())
+}
+
+/// Name of the init system, from the binary pid 1 runs or its name
+fn init(root: &Path) -> Option<String> {
+    // only readable as root, but follows `/sbin/init` links to the actual init
+    let exe = read_link(root.join("proc/1/exe")).ok().and_then(|exe| {
+        let name = exe.file_name()?.to_string_lossy().to_string();
+        // the binary was replaced by an upgrade without re-executing it
+        Some(name.trim_end_matches(" (deleted)").to_string())
+    });
+    let comm = read_to_string(root.join("proc/1/comm"))
+        .ok()
+        .map(|comm| comm.trim().to_string());
+    let name = exe.or(comm)?;
+
+    Some(
+        match name.as_str() {
+            "systemd" => "systemd",
+            "openrc-init" => "OpenRC",
+            "runit" | "runit-init" => "runit",
+            "s6-svscan" | "s6-linux-init" => "s6",
+            "dinit" => "dinit",
+            "busybox" => "BusyBox",
+            "init" if root.join("run/openrc").is_dir() => "OpenRC",
+            "init" if root.join("run/runit").is_dir() => "runit",
+            "init" => "SysVinit",
+            // containers often run a plain program as pid 1
+            name => name,
+        }
+        .to_string(),
+    )
+}
+
+impl Render for BootInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(init) = &self.init {
+            lines.push(format!("{}: {init}", "Init".bold().color(color)));
+        }
+
+        let mut boot = Vec::new();
+        match self.mode {
+            Some(BootMode::Uefi) => {
+                boot.push("UEFI".to_string());
+                match self.secure_boot {
+                    Some(true) => boot.push("secure boot on".to_string()),
+                    Some(false) => boot.push("secure boot off".to_string()),
+                    None => {}
+                }
+            }
+            Some(BootMode::Bios) => boot.push("BIOS".to_string()),
+            None => {}
+        }
+        boot.extend(self.bootloader.clone());
+        if !boot.is_empty() {
+            lines.push(format!(
+                "{}: {}",
+                "Boot".bold().color(color),
+                boot.join(", ")
+            ));
+        }
+
+        if let Some(cmdline) = self.cmdline.as_ref().filter(|_| self.show_cmdline) {
+            lines.push(format!(
+                "{}: {cmdline}",
+                "Kernel cmdline".bold().color(color)
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for BootInfo {
+    const ID: &'static str = "boot";
+
+    fn from_system(_: &System) -> Self {
+        Self::from_root(Path::new("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    fn render(info: &BootInfo) -> Vec<String> {
+        info.render(DynColors::Ansi(AnsiColors::Default))
+    }
+
+    #[test]
+    fn uefi_systemd_boot() {
+        let root = Fixture::new::<&[u8]>(&[
+            ("proc/1/comm", b"systemd\n"),
+            (
+                "proc/cmdline",
+                b"initrd=\\initramfs-linux.img root=UUID=1234 rw quiet\n",
+            ),
+            (
+                "sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c",
+                &[6, 0, 0, 0, 1],
+            ),
+        ]);
+        let loader: Vec<u8> = [6, 0, 0, 0]
+            .into_iter()
+            .chain(
+                "systemd-boot 255.4\0"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect();
+        root.write(
+            "sys/firmware/efi/efivars/LoaderInfo-4a67b082-0a4c-41cf-b6c7-440b29bb8c4f",
+            loader,
+        );
+
+        let info = BootInfo::from_root(&root);
+        assert_eq!(
+            info,
+            BootInfo {
+                init: Some("systemd".to_string()),
+                mode: Some(BootMode::Uefi),
+                secure_boot: Some(true),
+                bootloader: Some("systemd-boot 255.4".to_string()),
+                cmdline: Some("initrd=\\initramfs-linux.img root=UUID=1234 rw quiet".to_string()),
+                show_cmdline: false,
+            }
+        );
+        let lines = render(&info);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].ends_with("UEFI, secure boot on, systemd-boot 255.4"));
+
+        let lines = render(&BootInfo {
+            show_cmdline: true,
+            ..info
+        });
+        assert!(lines[2].ends_with("root=UUID=1234 rw quiet"));
+    }
+
+    #[test]
+    fn bios_grub_openrc() {
+        let root = Fixture::new(&[
+            ("proc/1/comm", "init\n"),
+            ("run/openrc/softlevel", "default\n"),
+            ("sys/firmware/acpi/tables/DSDT", ""),
+            ("boot/grub/grub.cfg", "set timeout=5\n"),
+        ]);
+
+        let info = BootInfo::from_root(&root);
+        assert_eq!(info.init.as_deref(), Some("OpenRC"));
+        assert_eq!(info.mode, Some(BootMode::Bios));
+        assert_eq!(info.secure_boot, None);
+        assert!(render(&info)[1].ends_with("BIOS, GRUB"));
+    }
+
+    #[test]
+    fn upgraded_init() {
+        let root = Fixture::new(&[("proc/1/comm", "init\n")]);
+        std::os::unix::fs::symlink(
+            "/usr/lib/systemd/systemd (deleted)",
+            root.join("proc/1/exe"),
+        )
+        .unwrap();
+        assert_eq!(init(&root).as_deref(), Some("systemd"));
+    }
+
+    #[test]
+    fn container() {
+        let root = Fixture::new(&[("proc/1/comm", "tini\n")]);
+        let info = BootInfo::from_root(&root);
+        assert_eq!(info.mode, None);
+        assert_eq!(render(&info).len(), 1);
+    }
+}
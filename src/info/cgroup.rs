@@ -0,0 +1,379 @@
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// cgroup v1 reports no memory limit as a page aligned `i64::MAX`, anything this large is unset
+const V1_UNLIMITED: u64 = 1 << 62;
+/// systemd caps every user slice at 33 % and every service at 15 % of the tasks the host allows,
+/// so only pids limits below this share of them are shown
+const PIDS_SHOWN_BELOW: f64 = 0.1;
+
+/// Memory of a cgroup with a limit, in bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryLimit {
+    pub limit: u64,
+    /// Memory charged to the cgroup, including page cache
+    pub usage: u64,
+    /// Page cache part of the usage
+    pub cache: u64,
+}
+
+impl MemoryLimit {
+    /// Usage without the page cache, which the kernel reclaims before hitting the limit
+    pub fn used(&self) -> u64 {
+        self.usage.saturating_sub(self.cache)
+    }
+}
+
+/// Resource limits of the cgroup punfetch runs in, from cgroup v2 or the v1 controllers
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CgroupLimits {
+    pub memory: Option<MemoryLimit>,
+    /// Cpu time quota, in cpus
+    pub cpus: Option<f64>,
+    /// Processes as `(current, max)`
+    pub pids: Option<(u64, u64)>,
+}
+
+impl CgroupLimits {
+    /// Read the limits of the current process under the given root, the tightest of its cgroup
+    /// and the ancestors of it. Cpu and pids limits the host doesn't reach anyway are left out.
+    pub fn from_root(root: &Path) -> Self {
+        let cgroups = read_to_string(root.join("proc/self/cgroup")).unwrap_or_default();
+        let mount = root.join("sys/fs/cgroup");
+
+        let mut limits = Self::default();
+        for line in cgroups.lines() {
+            // hierarchy-ID:controller-list:cgroup-path
+            let mut fields = line.splitn(3, ':').skip(1);
+            let (Some(controllers), Some(path)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+
+            if controllers.is_empty() {
+                // hybrid setups mount the unified hierarchy next to the v1 controllers
+                let mount = if mount.join("cgroup.controllers").exists() {
+                    mount.clone()
+                } else {
+                    mount.join("unified")
+                };
+                let dirs = hierarchy(&mount, path);
+                limits.memory = limits
+                    .memory
+                    .or_else(|| memory(&dirs, ["memory.max", "memory.current", "file"]));
+                limits.cpus = limits.cpus.or_else(|| {
+                    let (cpus, _) = tightest(&dirs, |dir| {
+                        let max = read_to_string(dir.join("cpu.max")).ok()?;
+                        let (quota, period) = max.trim().split_once(' ')?;
+                        Some(quota.parse::<f64>().ok()? / period.parse::<f64>().ok()?)
+                    })?;
+                    Some(cpus)
+                });
+                limits.pids = limits.pids.or_else(|| pids(&dirs));
+                continue;
+            }
+
+            let dirs = hierarchy(&mount.join(controllers), path);
+            for controller in controllers.split(',') {
+                match controller {
+                    "memory" => {
+                        limits.memory = limits.memory.or_else(|| {
+                            let files = [
+                                "memory.limit_in_bytes",
+                                "memory.usage_in_bytes",
+                                "total_cache",
+                            ];
+                            memory(&dirs, files)
+                        })
+                    }
+                    "cpu" => {
+                        limits.cpus = limits.cpus.or_else(|| {
+                            let (cpus, _) = tightest(&dirs, |dir| {
+                                let quota = number::<i64>(dir, "cpu.cfs_quota_us")?;
+                                let period = number::<i64>(dir, "cpu.cfs_period_us")?;
+                                (quota > 0 && period > 0).then(|| quota as f64 / period as f64)
+                            })?;
+                            Some(cpus)
+                        })
+                    }
+                    "pids" => limits.pids = limits.pids.or_else(|| pids(&dirs)),
+                    _ => {}
+                }
+            }
+        }
+
+        if let Ok(online) = read_to_string(root.join("sys/devices/system/cpu/online")) {
+            let host = cpu_list(&online).len() as f64;
+            limits.cpus = limits.cpus.filter(|&cpus| cpus < host);
+        }
+        let tasks = ["proc/sys/kernel/threads-max", "proc/sys/kernel/pid_max"]
+            .iter()
+            .filter_map(|file| {
+                read_to_string(root.join(file))
+                    .ok()?
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+            .min();
+        if let Some(tasks) = tasks {
+            limits.pids = limits
+                .pids
+                .filter(|&(_, max)| (max as f64) < tasks as f64 * PIDS_SHOWN_BELOW);
+        }
+        limits
+    }
+
+    /// Memory limit, unless it is no less than the host's memory
+    pub fn effective_memory(&self, host_total: u64) -> Option<MemoryLimit> {
+        self.memory.filter(|memory| memory.limit < host_total)
+    }
+
+    /// Cpus the quota allows, at most the host's logical cpus
+    pub fn effective_cpus(&self, host_cpus: usize) -> f64 {
+        let host = host_cpus.max(1) as f64;
+        self.cpus.map_or(host, |cpus| cpus.min(host))
+    }
+}
+
+/// Directories of a cgroup and its ancestors, up to the mount point of its hierarchy
+fn hierarchy(mount: &Path, path: &str) -> Vec<PathBuf> {
+    // inside a cgroup namespace the mount point already is the cgroup, while the path is the
+    // one on the host
+    let mut dir = mount.join(path.trim_start_matches('/'));
+    if !dir.is_dir() {
+        dir = mount.to_path_buf();
+    }
+    dir.ancestors()
+        .take_while(|dir| dir.starts_with(mount))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+fn number<T: FromStr>(dir: &Path, file: &str) -> Option<T> {
+    read_to_string(dir.join(file)).ok()?.trim().parse().ok()
+}
+
+/// Smallest limit set in any of the directories, with the directory setting it
+fn tightest<T: PartialOrd>(
+    dirs: &[PathBuf],
+    limit: impl Fn(&Path) -> Option<T>,
+) -> Option<(T, &Path)> {
+    dirs.iter()
+        .filter_map(|dir| Some((limit(dir)?, dir.as_path())))
+        .reduce(|a, b| if b.0 < a.0 { b } else { a })
+}
+
+/// Memory limit and usage, from the limit and usage files and the cache key of `memory.stat`.
+///
+/// The usage is the one of the cgroup setting the limit, which includes all of its children.
+fn memory(dirs: &[PathBuf], [max, current, cache]: [&str; 3]) -> Option<MemoryLimit> {
+    // `max` doesn't parse, so unset v2 limits are skipped too
+    let (limit, dir) = tightest(dirs, |dir| {
+        number::<u64>(dir, max).filter(|&limit| limit < V1_UNLIMITED)
+    })?;
+    let usage = number(dir, current)?;
+    let cache = read_to_string(dir.join("memory.stat"))
+        .unwrap_or_default()
+        .lines()
+        .find_map(|l| l.strip_prefix(cache)?.strip_prefix(' ')?.parse().ok())
+        .unwrap_or(0);
+    Some(MemoryLimit {
+        limit,
+        usage,
+        cache: cache.min(usage),
+    })
+}
+
+/// Processes in the cgroup setting the tightest pids limit, and that limit
+fn pids(dirs: &[PathBuf]) -> Option<(u64, u64)> {
+    let (max, dir) = tightest(dirs, |dir| number::<u64>(dir, "pids.max"))?;
+    Some((number(dir, "pids.current").unwrap_or(0), max))
+}
+
+impl Render for CgroupLimits {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(cpus) = self.cpus {
+            lines.push(format!(
+                "{}: {} cpus",
+                "Cpu limit".bold().color(color),
+                (cpus * 100.0).round() / 100.0
+            ));
+        }
+        if let Some((current, max)) = self.pids {
+            lines.push(format!(
+                "{}: {current} / {max}",
+                "Process limit".bold().color(color)
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for CgroupLimits {
+    const ID: &'static str = "cgroup";
+
+    fn from_system(_: &System) -> Self {
+        Self::from_root(Path::new("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    #[test]
+    fn unified() {
+        let root = Fixture::new(&[
+            ("proc/self/cgroup", "0::/user.slice/app.scope\n"),
+            ("sys/fs/cgroup/cgroup.controllers", "cpu memory pids\n"),
+            ("sys/fs/cgroup/user.slice/memory.max", "4294967296\n"),
+            ("sys/fs/cgroup/user.slice/memory.current", "2147483648\n"),
+            (
+                "sys/fs/cgroup/user.slice/memory.stat",
+                "anon 1610612736\nfile 536870912\n",
+            ),
+            ("sys/fs/cgroup/user.slice/cpu.max", "max 100000\n"),
+            ("sys/fs/cgroup/user.slice/app.scope/memory.max", "max\n"),
+            (
+                "sys/fs/cgroup/user.slice/app.scope/memory.current",
+                "1288490188\n",
+            ),
+            (
+                "sys/fs/cgroup/user.slice/app.scope/memory.stat",
+                "anon 1073741824\nfile 214748364\n",
+            ),
+            (
+                "sys/fs/cgroup/user.slice/app.scope/cpu.max",
+                "150000 100000\n",
+            ),
+            ("sys/fs/cgroup/user.slice/app.scope/pids.max", "512\n"),
+            ("sys/fs/cgroup/user.slice/app.scope/pids.current", "12\n"),
+        ]);
+
+        let limits = CgroupLimits::from_root(&root);
+        assert_eq!(
+            limits,
+            CgroupLimits {
+                // the usage of the slice setting the limit, not the one of the scope
+                memory: Some(MemoryLimit {
+                    limit: 4294967296,
+                    usage: 2147483648,
+                    cache: 536870912,
+                }),
+                cpus: Some(1.5),
+                pids: Some((12, 512)),
+            }
+        );
+        assert_eq!(limits.effective_cpus(8), 1.5);
+        assert_eq!(limits.effective_cpus(1), 1.0);
+        assert_eq!(limits.effective_memory(2 << 30), None);
+
+        let lines = limits.render(DynColors::Ansi(AnsiColors::Default));
+        assert!(lines[0].ends_with("1.5 cpus"));
+        assert!(lines[1].ends_with("12 / 512"));
+    }
+
+    #[test]
+    fn v1_namespaced() {
+        // the host path of the cgroup doesn't exist inside the namespace
+        let root = Fixture::new(&[
+            (
+                "proc/self/cgroup",
+                "4:memory:/docker/0123abcd\n3:cpu,cpuacct:/docker/0123abcd\n0::/\n",
+            ),
+            ("sys/fs/cgroup/unified/cgroup.procs", ""),
+            ("sys/fs/cgroup/memory/memory.limit_in_bytes", "536870912\n"),
+            ("sys/fs/cgroup/memory/memory.usage_in_bytes", "268435456\n"),
+            (
+                "sys/fs/cgroup/memory/memory.stat",
+                "cache 1048576\ntotal_cache 67108864\n",
+            ),
+            ("sys/fs/cgroup/cpu,cpuacct/cpu.cfs_quota_us", "200000\n"),
+            ("sys/fs/cgroup/cpu,cpuacct/cpu.cfs_period_us", "100000\n"),
+        ]);
+
+        let limits = CgroupLimits::from_root(&root);
+        let memory = limits.effective_memory(16 << 30).unwrap();
+        assert_eq!(memory.limit, 512 << 20);
+        assert_eq!(memory.used(), 192 << 20);
+        assert_eq!(limits.cpus, Some(2.0));
+        assert_eq!(limits.pids, None);
+    }
+
+    #[test]
+    fn host_values() {
+        let root = Fixture::new(&[
+            (
+                "proc/self/cgroup",
+                "0::/user.slice/user-1000.slice/session-2.scope\n",
+            ),
+            ("proc/sys/kernel/threads-max", "127431\n"),
+            ("proc/sys/kernel/pid_max", "4194304\n"),
+            ("sys/devices/system/cpu/online", "0-7\n"),
+            ("sys/fs/cgroup/cgroup.controllers", "cpu memory pids\n"),
+            (
+                "sys/fs/cgroup/user.slice/user-1000.slice/session-2.scope/cgroup.procs",
+                "1234\n",
+            ),
+            // systemd's default TasksMax of 33 %
+            (
+                "sys/fs/cgroup/user.slice/user-1000.slice/pids.max",
+                "42052\n",
+            ),
+            (
+                "sys/fs/cgroup/user.slice/user-1000.slice/pids.current",
+                "412\n",
+            ),
+            ("sys/fs/cgroup/user.slice/cpu.max", "800000 100000\n"),
+        ]);
+        assert_eq!(CgroupLimits::from_root(&root), CgroupLimits::default());
+
+        root.write("sys/fs/cgroup/user.slice/cpu.max", "400000 100000\n");
+        root.write(
+            "sys/fs/cgroup/user.slice/user-1000.slice/session-2.scope/pids.max",
+            "512\n",
+        );
+        root.write(
+            "sys/fs/cgroup/user.slice/user-1000.slice/session-2.scope/pids.current",
+            "7\n",
+        );
+        let limits = CgroupLimits::from_root(&root);
+        assert_eq!(limits.cpus, Some(4.0));
+        assert_eq!(limits.pids, Some((7, 512)));
+    }
+
+    #[test]
+    fn unlimited() {
+        let root = Fixture::new(&[
+            ("proc/self/cgroup", "4:memory:/\n1:cpu:/\n"),
+            (
+                "sys/fs/cgroup/memory/memory.limit_in_bytes",
+                "9223372036854771712\n",
+            ),
+            ("sys/fs/cgroup/memory/memory.usage_in_bytes", "4594270208\n"),
+            ("sys/fs/cgroup/cpu/cpu.cfs_quota_us", "-1\n"),
+            ("sys/fs/cgroup/cpu/cpu.cfs_period_us", "100000\n"),
+        ]);
+
+        let limits = CgroupLimits::from_root(&root);
+        assert_eq!(limits, CgroupLimits::default());
+        assert_eq!(limits.effective_cpus(4), 4.0);
+        assert!(limits
+            .render(DynColors::Ansi(AnsiColors::Default))
+            .is_empty());
+    }
+}
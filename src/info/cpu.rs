@@ -0,0 +1,478 @@
+use std::{collections::BTreeSet, env, fs::read_to_string, path::Path};
+
+use byte_unit::Byte;
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::{CpuExt, CpuRefreshKind, RefreshKind, System, SystemExt};
+
+use super::*;
+
+/// Flags worth pointing out, x86 names first then their arm equivalents
+pub const NOTABLE_FLAGS: [&str; 10] = [
+    "avx2",
+    "avx512f",
+    "aes",
+    "sha_ni",
+    "vmx",
+    "svm",
+    "hypervisor",
+    "asimd",
+    "sve",
+    "sha2",
+];
+
+/// Model, topology, frequency and architecture of the cpus
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CpuInfo {
+    pub model: String,
+    pub arch: String,
+    pub sockets: usize,
+    /// Physical cores, across all sockets
+    pub cores: usize,
+    /// Logical cpus, across all sockets
+    pub threads: usize,
+    /// Performance and efficiency cores on hybrid cpus
+    pub hybrid: Option<(usize, usize)>,
+    /// Average current frequency, in MHz
+    pub current_mhz: Option<u64>,
+    /// Highest maximum frequency of any core, in MHz
+    pub max_mhz: Option<u64>,
+    /// Total size of each cache level, e.g. `("L2", 2097152)`
+    pub caches: Vec<(String, u64)>,
+    /// Flags from [`NOTABLE_FLAGS`] the cpu has
+    pub flags: Vec<String>,
+    /// Show the caches and flags
+    pub details: bool,
+}
+
+/// Logical cpu from `/proc/cpuinfo`, with its topology when known
+#[derive(Default)]
+struct Processor {
+    id: usize,
+    package: Option<String>,
+    core: Option<String>,
+    mhz: Option<f64>,
+}
+
+impl CpuInfo {
+    /// Read cpu details from `/proc/cpuinfo` and `/sys/devices/system/cpu` under the given root
+    pub fn from_root(root: &Path) -> Option<Self> {
+        let cpuinfo = read_to_string(root.join("proc/cpuinfo")).ok()?;
+        let sys_cpu = root.join("sys/devices/system/cpu");
+        let sysfs = |cpu: usize, file: &str| {
+            read_to_string(sys_cpu.join(format!("cpu{cpu}")).join(file))
+                .ok()
+                .map(|s| s.trim().to_string())
+        };
+
+        let mut model = None;
+        let mut flags = Vec::new();
+        let mut processors: Vec<Processor> = Vec::new();
+        for line in cpuinfo.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "processor" => processors.push(Processor {
+                    id: value.parse().unwrap_or(processors.len()),
+                    ..Default::default()
+                }),
+                // x86, then arm and powerpc
+                "model name" | "Hardware" | "cpu model" | "cpu" if model.is_none() => {
+                    model = Some(clean_model(value))
+                }
+                "flags" | "Features" if flags.is_empty() => {
+                    flags = value.split_whitespace().map(String::from).collect()
+                }
+                _ => {}
+            }
+            if let Some(processor) = processors.last_mut() {
+                match key {
+                    "physical id" => processor.package = Some(value.to_string()),
+                    "core id" => processor.core = Some(value.to_string()),
+                    "cpu MHz" => processor.mhz = value.parse().ok(),
+                    _ => {}
+                }
+            }
+        }
+
+        // sysfs topology covers architectures that don't report it in cpuinfo
+        for processor in &mut processors {
+            if let Some(package) = sysfs(processor.id, "topology/physical_package_id") {
+                processor.package = Some(package);
+            }
+            if let Some(core) = sysfs(processor.id, "topology/core_id") {
+                processor.core = Some(core);
+            }
+        }
+
+        let threads = processors.len().max(1);
+        let sockets = processors
+            .iter()
+            .map(|p| &p.package)
+            .collect::<BTreeSet<_>>()
+            .len()
+            .max(1);
+        let cores_of = |cpus: &[usize]| {
+            processors
+                .iter()
+                .filter(|p| cpus.contains(&p.id))
+                .map(|p| {
+                    (
+                        &p.package,
+                        p.core.clone().unwrap_or_else(|| p.id.to_string()),
+                    )
+                })
+                .collect::<BTreeSet<_>>()
+                .len()
+        };
+        let ids: Vec<usize> = processors.iter().map(|p| p.id).collect();
+        let cores = cores_of(&ids).max(1);
+
+        // intel hybrid cpus list their performance and efficiency cpus as separate pmus
+        let hybrid = match (
+            read_to_string(root.join("sys/devices/cpu_core/cpus")),
+            read_to_string(root.join("sys/devices/cpu_atom/cpus")),
+        ) {
+            (Ok(p), Ok(e)) => Some((cores_of(&cpu_list(&p)), cores_of(&cpu_list(&e)))),
+            _ => None,
+        };
+
+        let khz = |file: &str| -> Vec<u64> {
+            ids.iter()
+                .filter_map(|&id| sysfs(id, file)?.parse().ok())
+                .collect()
+        };
+        let max_mhz = khz("cpufreq/cpuinfo_max_freq")
+            .into_iter()
+            .max()
+            .map(|f| f / 1000);
+        let current = khz("cpufreq/scaling_cur_freq");
+        let current_mhz = if current.is_empty() {
+            let mhz: Vec<f64> = processors.iter().filter_map(|p| p.mhz).collect();
+            (!mhz.is_empty()).then(|| (mhz.iter().sum::<f64>() / mhz.len() as f64) as u64)
+        } else {
+            Some(current.iter().sum::<u64>() / current.len() as u64 / 1000)
+        };
+
+        let mut caches = caches(&sys_cpu, &ids);
+        if caches.is_empty() {
+            if let Some(size) = cpuinfo
+                .lines()
+                .find_map(|l| l.strip_prefix("cache size")?.trim().strip_prefix(':'))
+                .and_then(|size| size.trim().strip_suffix("KB")?.trim().parse::<u64>().ok())
+            {
+                caches.push(("Cache".to_string(), size * 1024));
+            }
+        }
+
+        Some(Self {
+            model: model.unwrap_or_else(|| "Unknown".to_string()),
+            arch: read_to_string(root.join("proc/sys/kernel/arch"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|_| env::consts::ARCH.to_string()),
+            sockets,
+            cores,
+            threads,
+            hybrid,
+            current_mhz,
+            max_mhz,
+            caches,
+            flags: NOTABLE_FLAGS
+                .iter()
+                .filter(|f| flags.iter().any(|flag| flag == *f))
+                .map(|f| f.to_string())
+                .collect(),
+            details: false,
+        })
+    }
+}
+
+/// Strip trademarks and the base clock from a model name, e.g.
+/// `Intel(R) Core(TM) i7-8700 CPU @ 3.20GHz` to `Intel Core i7-8700`
+fn clean_model(model: &str) -> String {
+    let model = model.split(" @ ").next().unwrap_or(model);
+    let words: Vec<&str> = model
+        .split_whitespace()
+        .map(|w| w.trim_end_matches("(R)").trim_end_matches("(TM)"))
+        .filter(|w| !matches!(*w, "" | "CPU" | "Processor") && !w.ends_with("-Core"))
+        .collect();
+    if words.is_empty() {
+        model.trim().to_string()
+    } else {
+        words.join(" ")
+    }
+}
+
+/// Parse a cpu list like `0-3,8,10-11`
+pub(crate) fn cpu_list(list: &str) -> Vec<usize> {
+    list.trim()
+        .split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((start, end)) => Some((start.parse().ok()?..=end.parse().ok()?).collect()),
+            None => Some(vec![range.parse().ok()?]),
+        })
+        .flatten()
+        .collect()
+}
+
+/// Total size of each cache level, counting caches shared between cpus once
+fn caches(sys_cpu: &Path, cpus: &[usize]) -> Vec<(String, u64)> {
+    let mut seen = BTreeSet::new();
+    let mut levels: Vec<(String, u64)> = Vec::new();
+    for cpu in cpus {
+        for index in 0.. {
+            let dir = sys_cpu.join(format!("cpu{cpu}/cache/index{index}"));
+            let read = |file: &str| read_to_string(dir.join(file)).ok();
+            let (Some(level), Some(size)) = (read("level"), read("size")) else {
+                break;
+            };
+            let kind = read("type").unwrap_or_default();
+            let shared = read("shared_cpu_list").unwrap_or_else(|| cpu.to_string());
+            if !seen.insert((level.clone(), kind, shared)) {
+                continue;
+            }
+
+            let Some(size) = size
+                .trim()
+                .strip_suffix('K')
+                .and_then(|k| k.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let level = format!("L{}", level.trim());
+            match levels.iter_mut().find(|(l, _)| *l == level) {
+                Some((_, total)) => *total += size * 1024,
+                None => levels.push((level, size * 1024)),
+            }
+        }
+    }
+    levels.sort();
+    levels
+}
+
+fn ghz(mhz: u64) -> String {
+    format!("{:.2} GHz", mhz as f64 / 1000.0)
+}
+
+impl Render for CpuInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let sockets = if self.sockets > 1 {
+            format!("{}x ", self.sockets)
+        } else {
+            String::new()
+        };
+        let cores = match self.hybrid {
+            Some((p, e)) => format!("{p}P+{e}E"),
+            None => format!(
+                "{} core{}",
+                self.cores,
+                if self.cores == 1 { "" } else { "s" }
+            ),
+        };
+        let frequency = match (self.current_mhz, self.max_mhz) {
+            (Some(current), Some(max)) if current < max => {
+                format!(" @ {} / {}", ghz(current), ghz(max))
+            }
+            (_, Some(mhz)) | (Some(mhz), None) => format!(" @ {}", ghz(mhz)),
+            (None, None) => String::new(),
+        };
+
+        let mut lines = vec![
+            format!(
+                "{}: {sockets}{} ({cores}, {} thread{}){frequency}",
+                "Cpu".bold().color(color),
+                self.model,
+                self.threads,
+                if self.threads == 1 { "" } else { "s" },
+            ),
+            format!("{}: {}", "Architecture".bold().color(color), self.arch),
+        ];
+        if self.details {
+            if !self.caches.is_empty() {
+                let caches: Vec<String> = self
+                    .caches
+                    .iter()
+                    .map(|(level, size)| {
+                        let size = Byte::from_bytes(*size as u128).get_appropriate_unit(true);
+                        format!("{level} {size}")
+                    })
+                    .collect();
+                lines.push(format!(
+                    "{}: {}",
+                    "Cpu cache".bold().color(color),
+                    caches.join(", ")
+                ));
+            }
+            if !self.flags.is_empty() {
+                lines.push(format!(
+                    "{}: {}",
+                    "Cpu flags".bold().color(color),
+                    self.flags.join(" ")
+                ));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for CpuInfo {
+    const ID: &'static str = "cpu";
+    const TTL: Option<Duration> = Some(Duration::from_secs(7 * 24 * 60 * 60));
+
+    fn cache_hint() -> String {
+        boot_hint()
+    }
+
+    // only used when /proc isn't readable
+    fn refresh_kind() -> RefreshKind {
+        RefreshKind::new().with_cpu(CpuRefreshKind::new().with_frequency())
+    }
+
+    fn from_system(sys: &System) -> Self {
+        Self::from_root(Path::new("/")).unwrap_or_else(|| {
+            let cpus = sys.cpus();
+            Self {
+                model: cpus
+                    .first()
+                    .map(|c| clean_model(c.brand()))
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                arch: env::consts::ARCH.to_string(),
+                sockets: 1,
+                cores: sys.physical_core_count().unwrap_or(cpus.len()),
+                threads: cpus.len(),
+                current_mhz: cpus.first().map(|c| c.frequency()),
+                ..Default::default()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    /// Cpuinfo block for one logical cpu
+    fn processor(id: usize, package: usize, core: usize) -> String {
+        format!(
+            "processor\t: {id}
+model name\t: Intel(R) Xeon(R) Gold 6248 CPU @ 2.50GHz
+flags\t\t: fpu avx2 avx512f aes hypervisor
+cpu MHz\t\t: 2500.000
+cache size\t: 28160 KB
+physical id\t: {package}
+core id\t\t: {core}
+
+"
+        )
+    }
+
+    fn render(cpu: &CpuInfo) -> Vec<String> {
+        cpu.render(DynColors::Ansi(AnsiColors::Default))
+    }
+
+    #[test]
+    fn multi_socket() {
+        // 2 sockets, 2 cores each, 2 threads per core
+        let cpuinfo: String = (0..8).map(|id| processor(id, id / 4, id % 2)).collect();
+        let root = Fixture::new(&[
+            ("proc/cpuinfo", cpuinfo),
+            ("proc/sys/kernel/arch", "x86_64\n".to_string()),
+        ]);
+
+        let cpu = CpuInfo::from_root(&root).unwrap();
+        assert_eq!(cpu.model, "Intel Xeon Gold 6248");
+        assert_eq!((cpu.sockets, cpu.cores, cpu.threads), (2, 4, 8));
+        assert_eq!(cpu.current_mhz, Some(2500));
+        assert_eq!(cpu.caches, [("Cache".to_string(), 28160 * 1024)]);
+        assert_eq!(cpu.flags, ["avx2", "avx512f", "aes", "hypervisor"]);
+        assert!(
+            render(&cpu)[0].ends_with("2x Intel Xeon Gold 6248 (4 cores, 8 threads) @ 2.50 GHz")
+        );
+    }
+
+    #[test]
+    fn hybrid_from_sysfs() {
+        // 2 performance cores with 2 threads each, then 4 efficiency cores
+        let mut files = vec![
+            (
+                "proc/cpuinfo".to_string(),
+                (0..8)
+                    .map(|id| format!("processor\t: {id}\nmodel name\t: 13th Gen Intel(R) Core(TM) i5-1340P\n\n"))
+                    .collect::<String>(),
+            ),
+            ("sys/devices/cpu_core/cpus".to_string(), "0-3\n".to_string()),
+            ("sys/devices/cpu_atom/cpus".to_string(), "4-7\n".to_string()),
+        ];
+        for id in 0..8 {
+            let cpu = format!("sys/devices/system/cpu/cpu{id}");
+            let core = if id < 4 { id / 2 } else { id };
+            files.push((
+                format!("{cpu}/topology/physical_package_id"),
+                "0".to_string(),
+            ));
+            files.push((format!("{cpu}/topology/core_id"), core.to_string()));
+            files.push((
+                format!("{cpu}/cpufreq/cpuinfo_max_freq"),
+                (4_600_000 - id * 100_000).to_string(),
+            ));
+            files.push((
+                format!("{cpu}/cpufreq/scaling_cur_freq"),
+                "1200000".to_string(),
+            ));
+            files.push((format!("{cpu}/cache/index0/level"), "1".to_string()));
+            files.push((format!("{cpu}/cache/index0/type"), "Data".to_string()));
+            files.push((format!("{cpu}/cache/index0/size"), "48K".to_string()));
+            files.push((
+                format!("{cpu}/cache/index0/shared_cpu_list"),
+                id.to_string(),
+            ));
+            files.push((format!("{cpu}/cache/index1/level"), "3".to_string()));
+            files.push((format!("{cpu}/cache/index1/type"), "Unified".to_string()));
+            files.push((format!("{cpu}/cache/index1/size"), "12288K".to_string()));
+            files.push((
+                format!("{cpu}/cache/index1/shared_cpu_list"),
+                "0-7".to_string(),
+            ));
+        }
+        let files: Vec<(&str, String)> =
+            files.iter().map(|(p, c)| (p.as_str(), c.clone())).collect();
+        let root = Fixture::new(&files);
+
+        let cpu = CpuInfo {
+            details: true,
+            ..CpuInfo::from_root(&root).unwrap()
+        };
+        assert_eq!(cpu.model, "13th Gen Intel Core i5-1340P");
+        assert_eq!((cpu.cores, cpu.threads, cpu.hybrid), (6, 8, Some((2, 4))));
+        assert_eq!((cpu.current_mhz, cpu.max_mhz), (Some(1200), Some(4600)));
+        assert_eq!(
+            cpu.caches,
+            [
+                ("L1".to_string(), 8 * 48 * 1024),
+                ("L3".to_string(), 12288 * 1024)
+            ]
+        );
+
+        let lines = render(&cpu);
+        assert!(lines[0].ends_with("(2P+4E, 8 threads) @ 1.20 GHz / 4.60 GHz"));
+        assert!(lines
+            .iter()
+            .any(|l| l.ends_with("L1 384.00 KiB, L3 12.00 MiB")));
+    }
+
+    #[test]
+    fn model_names() {
+        assert_eq!(
+            clean_model("AMD Ryzen 7 5800X 8-Core Processor"),
+            "AMD Ryzen 7 5800X"
+        );
+        assert_eq!(clean_model("Intel(R) Xeon(R) Processor"), "Intel Xeon");
+        assert_eq!(cpu_list("0-2,5,7-8\n"), [0, 1, 2, 5, 7, 8]);
+    }
+}
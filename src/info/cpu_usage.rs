@@ -0,0 +1,160 @@
+use std::{fs::read_to_string, path::Path, thread::sleep, time::Duration};
+
+use owo_colors::{colored::Color, DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+pub const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+/// Heat strip colors, from idle to fully busy
+pub const HEAT_COLORS: [DynColors; 5] = [
+    DynColors::Ansi(Color::Blue),
+    DynColors::Ansi(Color::Cyan),
+    DynColors::Ansi(Color::Green),
+    DynColors::Ansi(Color::Yellow),
+    DynColors::Ansi(Color::Red),
+];
+
+/// Cpu utilization sampled from `/proc/stat`, overall and per core, as fractions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CpuUsage {
+    pub total: f64,
+    pub cores: Vec<f64>,
+}
+
+/// Busy and total jiffies of each `cpu` line in `/proc/stat`, the aggregate line first
+fn jiffies(root: &Path) -> Option<Vec<(u64, u64)>> {
+    let stat = read_to_string(root.join("proc/stat")).ok()?;
+    let cpus: Vec<(u64, u64)> = stat
+        .lines()
+        .filter(|l| l.starts_with("cpu"))
+        .map(|line| {
+            // user nice system idle iowait irq softirq steal, guest time is already in user
+            let fields: Vec<u64> = line
+                .split_whitespace()
+                .skip(1)
+                .take(8)
+                .map(|f| f.parse().unwrap_or(0))
+                .collect();
+            let total = fields.iter().sum();
+            let idle = fields.iter().skip(3).take(2).sum::<u64>();
+            (total - idle, total)
+        })
+        .collect();
+    (!cpus.is_empty()).then_some(cpus)
+}
+
+impl CpuUsage {
+    /// Utilization between two readings of [`jiffies`]
+    fn between(before: &[(u64, u64)], after: &[(u64, u64)]) -> Self {
+        let mut usage = before.iter().zip(after).map(|((b0, t0), (b1, t1))| {
+            let total = t1.saturating_sub(*t0);
+            if total == 0 {
+                0.0
+            } else {
+                b1.saturating_sub(*b0) as f64 / total as f64
+            }
+        });
+        Self {
+            total: usage.next().unwrap_or_default(),
+            cores: usage.collect(),
+        }
+    }
+
+    /// Sample `/proc/stat` under the given root twice, `interval` apart.
+    ///
+    /// This blocks for the whole interval, so it should run on the [`Collector`] next to the
+    /// other modules.
+    pub fn sample(root: &Path, interval: Duration) -> Option<Self> {
+        let before = jiffies(root)?;
+        sleep(interval);
+        Some(Self::between(&before, &jiffies(root)?))
+    }
+}
+
+impl Render for CpuUsage {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        if self.cores.is_empty() {
+            return Vec::new();
+        }
+
+        let title = "Cpu usage";
+        let total = format!("{:.1} %", self.total * 100.0);
+        // continuation rows line up with the strip of the first one, after `Cpu usage: 12.5 % `
+        let indent = " ".repeat(title.len() + total.len() + 3);
+        let heat = |usage: f64| {
+            let i = (usage.clamp(0.0, 1.0) * HEAT_COLORS.len() as f64) as usize;
+            HEAT_COLORS[i.min(HEAT_COLORS.len() - 1)]
+        };
+        self.cores
+            .chunks(DEFAULT_BAR_WIDTH)
+            .map(|cores| ColorBar(cores.iter().map(|&u| heat(u)).collect()).strip(" "))
+            .enumerate()
+            .map(|(i, strip)| match i {
+                0 => format!("{}: {total} {strip}", title.bold().color(color)),
+                _ => format!("{indent}{strip}"),
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for CpuUsage {
+    const ID: &'static str = "cpu_usage";
+
+    fn from_system(_: &System) -> Self {
+        Self::sample(Path::new("/"), DEFAULT_SAMPLE_INTERVAL).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    #[test]
+    fn usage_between_readings() {
+        let root = Fixture::new(&[(
+            "proc/stat",
+            "cpu  100 0 100 700 100 0 0 0 0 0
+cpu0 50 0 50 350 50 0 0 0 0 0
+cpu1 50 0 50 350 50 0 0 0 0 0
+intr 1234
+",
+        )]);
+        let before = jiffies(&root).unwrap();
+        assert_eq!(before, [(200, 1000), (100, 500), (100, 500)]);
+
+        // cpu0 fully busy, cpu1 idle
+        root.write(
+            "proc/stat",
+            "cpu  200 0 100 800 100 0 0 0 0 0
+cpu0 150 0 50 350 50 0 0 0 0 0
+cpu1 50 0 50 450 50 0 0 0 0 0
+",
+        );
+        let usage = CpuUsage::between(&before, &jiffies(&root).unwrap());
+        assert_eq!(usage.total, 0.5);
+        assert_eq!(usage.cores, [1.0, 0.0]);
+
+        let lines = usage.render(DynColors::Ansi(AnsiColors::Default));
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("50.0 %"));
+    }
+
+    #[test]
+    fn sample_wraps_many_cores() {
+        let usage = CpuUsage {
+            total: 0.1,
+            cores: vec![0.1; DEFAULT_BAR_WIDTH + 1],
+        };
+        let lines = usage.render(DynColors::Ansi(AnsiColors::Default));
+        assert_eq!(lines.len(), 2);
+        // the second strip starts right under the first one
+        assert_eq!(lines[1].find('\x1b'), Some("Cpu usage: 10.0 % ".len()));
+        assert!(CpuUsage::sample(&Fixture::new::<&str>(&[]), Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,282 @@
+use byte_unit::Byte;
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::{Disk, DiskExt, RefreshKind, System, SystemExt};
+
+use super::*;
+
+/// Filesystems hidden by default, pseudo filesystems and read-only images like snaps
+pub const DEFAULT_IGNORED_FS: [&str; 7] = [
+    "tmpfs",
+    "devtmpfs",
+    "ramfs",
+    "squashfs",
+    "overlay",
+    "fuse.snapfuse",
+    "iso9660",
+];
+
+/// Filtering and display options for [`DiskInfo`]
+#[derive(Clone)]
+pub struct DiskOptions {
+    /// Filesystem types to leave out
+    pub ignored_fs: Vec<String>,
+    /// Leave out loop devices
+    pub ignore_loop: bool,
+    /// Only show mount points matching one of these globs, or all when empty
+    pub include: Vec<String>,
+    /// Leave out mount points matching any of these globs
+    pub exclude: Vec<String>,
+    /// Add a line per disk with its filesystem, used and total space
+    pub details: bool,
+    /// Style of the usage bar
+    pub style: BarStyle,
+    /// Layout of the legend under the bar
+    pub legend: Legend,
+    /// Usage at which a disk is highlighted
+    pub thresholds: Thresholds,
+}
+
+impl Default for DiskOptions {
+    fn default() -> Self {
+        Self {
+            ignored_fs: DEFAULT_IGNORED_FS.map(String::from).to_vec(),
+            ignore_loop: true,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            details: false,
+            style: BarStyle::default(),
+            legend: Legend::default(),
+            thresholds: DISK_THRESHOLDS,
+        }
+    }
+}
+
+/// A block device, with every mount point it is mounted on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub device: String,
+    pub mounts: Vec<String>,
+    pub fs: String,
+    pub total: u64,
+    pub used: u64,
+    pub removable: bool,
+}
+
+/// Disk usage bar, with optional per-disk lines
+pub struct DiskInfo {
+    pub disks: Vec<DiskUsage>,
+    pub details: bool,
+    pub style: BarStyle,
+    pub legend: Legend,
+    pub thresholds: Thresholds,
+}
+
+impl DiskInfo {
+    /// Filter mounts and group the ones sharing a device, e.g. bind mounts or btrfs subvolumes
+    pub fn new<I: IntoIterator<Item = DiskUsage>>(mounts: I, options: &DiskOptions) -> Self {
+        let mut disks: Vec<DiskUsage> = Vec::new();
+        for mount in mounts {
+            let Some(mount_point) = mount.mounts.first() else {
+                continue;
+            };
+            // pseudo filesystems missing from the ignore list report no size at all
+            if mount.total == 0
+                || options.ignored_fs.contains(&mount.fs)
+                || (options.ignore_loop && mount.device.starts_with("/dev/loop"))
+                || (!options.include.is_empty()
+                    && !options.include.iter().any(|p| glob(p, mount_point)))
+                || options.exclude.iter().any(|p| glob(p, mount_point))
+            {
+                continue;
+            }
+
+            match disks.iter_mut().find(|d| d.device == mount.device) {
+                Some(disk) => disk.mounts.extend(mount.mounts),
+                None => disks.push(mount),
+            }
+        }
+
+        for disk in &mut disks {
+            disk.mounts.sort_by_key(|m| (m.len(), m.clone()));
+            disk.mounts.dedup();
+        }
+
+        Self {
+            disks,
+            details: options.details,
+            style: options.style,
+            legend: options.legend,
+            thresholds: options.thresholds,
+        }
+    }
+
+    /// Usage bar with a legend entry per disk
+    pub fn bar(&self) -> PercentBar {
+        PercentBar {
+            title: "Disks".to_string(),
+            total: self.disks.iter().map(|d| d.total as f64).sum(),
+            items: self
+                .disks
+                .iter()
+                .map(|d| {
+                    let used = d.used as f64;
+                    (d.mounts.join(", "), used, used / d.total as f64)
+                })
+                .collect(),
+            colors: DEFAULT_COLORS.to_vec(),
+            width: DEFAULT_BAR_WIDTH,
+            style: self.style,
+            legend: self.legend,
+            thresholds: vec![Some(self.thresholds); self.disks.len()],
+        }
+    }
+
+    /// Usage of each disk against the thresholds
+    pub fn checks(&self) -> Vec<Check> {
+        self.disks
+            .iter()
+            .map(|d| {
+                let used = d.used as f64 / d.total as f64;
+                Check::new(
+                    format!("disk {}", d.mounts.join(", ")),
+                    used,
+                    self.thresholds,
+                )
+            })
+            .collect()
+    }
+}
+
+impl Render for DiskInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let mut lines = self.bar().render(color);
+        if self.details {
+            for disk in &self.disks {
+                lines.push(format!(
+                    "{}: {} / {} ({}{})",
+                    format!("Disk ({})", disk.mounts.join(", "))
+                        .bold()
+                        .color(color),
+                    Byte::from_bytes(disk.used as u128).get_appropriate_unit(true),
+                    Byte::from_bytes(disk.total as u128).get_appropriate_unit(true),
+                    disk.fs,
+                    if disk.removable { ", removable" } else { "" },
+                ));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl From<&Disk> for DiskUsage {
+    fn from(disk: &Disk) -> Self {
+        Self {
+            device: disk.name().to_string_lossy().to_string(),
+            mounts: vec![disk.mount_point().to_string_lossy().to_string()],
+            fs: String::from_utf8_lossy(disk.file_system()).to_string(),
+            total: disk.total_space(),
+            used: disk.total_space().saturating_sub(disk.available_space()),
+            removable: disk.is_removable(),
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+/// Disk usage bar with mountpoints and percents
+impl From<&[Disk]> for PercentBar {
+    fn from(disks: &[Disk]) -> Self {
+        DiskInfo::new(disks.iter().map(DiskUsage::from), &DiskOptions::default()).bar()
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl DiskInfo {
+    /// Disks from the system, filtered and grouped with the given options
+    pub fn with_options(sys: &System, options: &DiskOptions) -> Self {
+        Self::new(sys.disks().iter().map(DiskUsage::from), options)
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for DiskInfo {
+    const ID: &'static str = "disks";
+
+    fn refresh_kind() -> RefreshKind {
+        RefreshKind::new().with_disks_list()
+    }
+
+    fn from_system(sys: &System) -> Self {
+        Self::with_options(sys, &DiskOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::*;
+
+    fn mount(device: &str, mount: &str, fs: &str) -> DiskUsage {
+        DiskUsage {
+            device: device.to_string(),
+            mounts: vec![mount.to_string()],
+            fs: fs.to_string(),
+            total: 100,
+            used: 25,
+            removable: device.starts_with("/dev/sdb"),
+        }
+    }
+
+    fn mounts() -> Vec<DiskUsage> {
+        vec![
+            mount("/dev/nvme0n1p2", "/home", "btrfs"),
+            mount("/dev/nvme0n1p2", "/", "btrfs"),
+            mount("/dev/nvme0n1p1", "/boot", "vfat"),
+            mount("/dev/loop3", "/snap/core/123", "squashfs"),
+            mount("/dev/loop4", "/mnt/image", "ext4"),
+            mount("tmpfs", "/tmp", "tmpfs"),
+            mount("overlay", "/var/lib/docker/overlay2/abc/merged", "overlay"),
+            mount("/dev/sdb1", "/run/media/usb", "exfat"),
+            DiskUsage {
+                total: 0,
+                used: 0,
+                ..mount("portal", "/run/user/1000/doc", "fuse.portal")
+            },
+        ]
+    }
+
+    #[test]
+    fn filters_and_groups() {
+        let info = DiskInfo::new(mounts(), &DiskOptions::default());
+        let mounts: Vec<_> = info.disks.iter().map(|d| d.mounts.join(", ")).collect();
+        assert_eq!(mounts, ["/, /home", "/boot", "/run/media/usb"]);
+        assert_eq!(info.bar().total, 300.0);
+        assert!(info.checks().iter().all(|c| c.status() == Status::Normal));
+    }
+
+    #[test]
+    fn include_exclude() {
+        let options = DiskOptions {
+            include: vec!["/run/media/*".to_string(), "/boot".to_string()],
+            exclude: vec!["/boot".to_string()],
+            ..Default::default()
+        };
+        let info = DiskInfo::new(mounts(), &options);
+        assert_eq!(info.disks.len(), 1);
+        assert_eq!(info.disks[0].device, "/dev/sdb1");
+    }
+
+    #[test]
+    fn details() {
+        let options = DiskOptions {
+            details: true,
+            ..Default::default()
+        };
+        let lines = DiskInfo::new(mounts(), &options).render(DynColors::Ansi(AnsiColors::Default));
+        assert!(lines.iter().any(|l| l.contains("exfat, removable")));
+        assert!(lines.iter().any(|l| l.contains("(btrfs)")));
+    }
+}
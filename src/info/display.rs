@@ -0,0 +1,322 @@
+use std::{
+    fs::{read, read_dir, read_to_string},
+    path::Path,
+};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+const EDID_HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+/// Display descriptor tags
+const MONITOR_NAME: u8 = 0xfc;
+const UNSPECIFIED_TEXT: u8 = 0xfe;
+
+/// How displays are shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum DisplayStyle {
+    /// Hide displays
+    None,
+    /// A single line with every display, comma separated
+    #[default]
+    Compact,
+    /// A line per display, titled with its connector
+    Full,
+}
+
+/// Monitor details from the 128 byte EDID base block
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edid {
+    /// Three letter PNP id of the manufacturer, e.g. `DEL`
+    pub manufacturer: String,
+    pub product: u16,
+    /// Monitor name descriptor, or the text descriptors laptop panels use in its place
+    pub name: Option<String>,
+    /// Active pixels and refresh rate of the preferred timing
+    pub preferred: Option<(u32, u32, f64)>,
+}
+
+impl Edid {
+    /// Parse the base block, `None` when the header or checksum is wrong
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let block = bytes.get(..128)?;
+        if block[..8] != EDID_HEADER || block.iter().fold(0u8, |a, b| a.wrapping_add(*b)) != 0 {
+            return None;
+        }
+
+        // three 5 bit letters, 1 is `A`
+        let id = u16::from_be_bytes([block[8], block[9]]);
+        let manufacturer = [10, 5, 0]
+            .iter()
+            .map(|shift| char::from(b'@' + ((id >> shift) & 0x1f) as u8))
+            .collect();
+
+        let (mut name, mut texts, mut preferred) = (None, Vec::new(), None);
+        for descriptor in block[54..126].chunks(18) {
+            if descriptor[..2] != [0, 0] {
+                // the first detailed timing is the preferred one
+                preferred = preferred.or_else(|| timing(descriptor));
+                continue;
+            }
+            let text = || {
+                let text = String::from_utf8_lossy(&descriptor[5..]);
+                let text = text.split('\n').next().unwrap_or_default().trim();
+                (!text.is_empty()).then(|| text.to_string())
+            };
+            match descriptor[3] {
+                MONITOR_NAME => name = name.or_else(text),
+                UNSPECIFIED_TEXT => texts.extend(text()),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            manufacturer,
+            product: u16::from_le_bytes([block[10], block[11]]),
+            name: name.or_else(|| (!texts.is_empty()).then(|| texts.join(" "))),
+            preferred,
+        })
+    }
+}
+
+/// Active pixels and refresh rate of a detailed timing descriptor
+fn timing(d: &[u8]) -> Option<(u32, u32, f64)> {
+    let clock = u16::from_le_bytes([d[0], d[1]]) as f64 * 10_000.0;
+    let high = |byte: u8, low: u8| (byte as u32) | (((low >> 4) as u32) << 8);
+    let low = |byte: u8, low: u8| (byte as u32) | (((low & 0xf) as u32) << 8);
+    let (width, height) = (high(d[2], d[4]), high(d[5], d[7]));
+    let (hblank, vblank) = (low(d[3], d[4]), low(d[6], d[7]));
+
+    let total = ((width + hblank) * (height + vblank)) as f64;
+    (total > 0.0).then(|| (width, height, clock / total))
+}
+
+/// A connected monitor
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    /// Connector it is plugged into, e.g. `eDP-1` or `HDMI-A-1`
+    pub connector: String,
+    pub name: Option<String>,
+    /// Preferred mode of the connector, which sysfs reports instead of the mode in use
+    pub resolution: Option<(u32, u32)>,
+    pub refresh: Option<f64>,
+}
+
+impl Monitor {
+    /// e.g. `DELL U2415 (1920x1200 @ 60 Hz)`
+    fn describe(&self) -> String {
+        let mode = match (self.resolution, self.refresh) {
+            (Some((w, h)), Some(hz)) => Some(format!("{w}x{h} @ {hz:.0} Hz")),
+            (Some((w, h)), None) => Some(format!("{w}x{h}")),
+            _ => None,
+        };
+        match (&self.name, mode) {
+            (Some(name), Some(mode)) => format!("{name} ({mode})"),
+            (None, Some(mode)) => mode,
+            (name, None) => name.clone().unwrap_or_else(|| self.connector.clone()),
+        }
+    }
+}
+
+/// Monitors connected to any gpu, from the DRM connectors in `/sys/class/drm`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DisplayInfo {
+    pub monitors: Vec<Monitor>,
+    pub style: DisplayStyle,
+}
+
+impl DisplayInfo {
+    /// Read the connected connectors under `/sys/class/drm` of the given root
+    pub fn from_root(root: &Path) -> Self {
+        let mut connectors: Vec<_> = read_dir(root.join("sys/class/drm"))
+            .map(|dir| dir.filter_map(|e| Some(e.ok()?.path())).collect())
+            .unwrap_or_default();
+        connectors.sort();
+
+        let monitors = connectors
+            .iter()
+            .filter_map(|path| {
+                // `card0-eDP-1`, next to the `card0` device itself
+                let name = path.file_name()?.to_string_lossy();
+                let (card, connector) = name.split_once('-')?;
+                if !card.starts_with("card") {
+                    return None;
+                }
+                let status = read_to_string(path.join("status")).ok()?;
+                (status.trim() == "connected").then(|| monitor(path, connector))
+            })
+            .collect();
+        Self {
+            monitors,
+            style: DisplayStyle::default(),
+        }
+    }
+}
+
+fn monitor(path: &Path, connector: &str) -> Monitor {
+    let edid = read(path.join("edid")).ok().and_then(|e| Edid::parse(&e));
+    let preferred = edid.as_ref().and_then(|e| e.preferred);
+
+    // the first mode is the preferred one, the mode in use is only known to the drm master
+    let resolution = read_to_string(path.join("modes"))
+        .ok()
+        .and_then(|modes| {
+            let (w, h) = modes.lines().next()?.split_once('x')?;
+            // interlaced modes end in `i`
+            Some((w.parse().ok()?, h.trim_end_matches('i').parse().ok()?))
+        })
+        .or_else(|| preferred.map(|(w, h, _)| (w, h)));
+    let refresh = preferred
+        .filter(|(w, h, _)| Some((*w, *h)) == resolution)
+        .map(|(_, _, hz)| hz);
+
+    Monitor {
+        connector: connector.to_string(),
+        name: edid.map(|e| {
+            e.name
+                .unwrap_or_else(|| format!("{} {:04X}", e.manufacturer, e.product))
+        }),
+        resolution,
+        refresh,
+    }
+}
+
+impl Render for DisplayInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        if self.monitors.is_empty() {
+            return Vec::new();
+        }
+        match self.style {
+            DisplayStyle::None => Vec::new(),
+            DisplayStyle::Compact => {
+                let monitors: Vec<String> = self.monitors.iter().map(Monitor::describe).collect();
+                vec![format!(
+                    "{}: {}",
+                    "Display".bold().color(color),
+                    monitors.join(", ")
+                )]
+            }
+            DisplayStyle::Full => self
+                .monitors
+                .iter()
+                .map(|monitor| {
+                    format!(
+                        "{}: {}",
+                        format!("Display ({})", monitor.connector)
+                            .bold()
+                            .color(color),
+                        monitor.describe()
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for DisplayInfo {
+    const ID: &'static str = "display";
+
+    fn from_system(_: &System) -> Self {
+        Self::from_root(Path::new("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    /// Dell U2415, 1920x1200 at 60 Hz
+    const DELL: [&str; 4] = [
+        "00ffffffffffff0010acc4a04c423530141c0104a53420783a1ec5ae4f34b126",
+        "0e5054a54b0001010101010101010101010101010101283c80a070b023403020",
+        "360008b01000001a000000ff00434656394e35423342304c4c0a000000fc0044",
+        "454c4c2055323431350a2020000000fd00384c1e5111000a2020202020200071",
+    ];
+
+    /// BOE laptop panel, named by text descriptors only
+    const BOE: [&str; 4] = [
+        "00ffffffffffff0009e5ca0b00000000141c0104a53420783a1ec5ae4f34b126",
+        "0e5054a54b00010101010101010101010101010101016c57d0a080e028503020",
+        "360008b01000001a000000fe00424f452043510a202020202020000000fe004e",
+        "4531333546424d2d4e34310a0000001000000000000000000000000000000008",
+    ];
+
+    fn blob(hex: [&str; 4]) -> Vec<u8> {
+        let hex = hex.concat();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn edid() {
+        let dell = Edid::parse(&blob(DELL)).unwrap();
+        assert_eq!(dell.manufacturer, "DEL");
+        assert_eq!(dell.product, 0xa0c4);
+        assert_eq!(dell.name.as_deref(), Some("DELL U2415"));
+        let (w, h, hz) = dell.preferred.unwrap();
+        assert_eq!((w, h), (1920, 1200));
+        assert!((hz - 59.95).abs() < 0.01);
+
+        let boe = Edid::parse(&blob(BOE)).unwrap();
+        assert_eq!(boe.name.as_deref(), Some("BOE CQ NE135FBM-N41"));
+        assert_eq!(boe.preferred.map(|(w, h, _)| (w, h)), Some((2256, 1504)));
+
+        let mut corrupt = blob(DELL);
+        corrupt[20] ^= 1;
+        assert_eq!(Edid::parse(&corrupt), None);
+        assert_eq!(Edid::parse(&blob(DELL)[..100]), None);
+    }
+
+    #[test]
+    fn connectors() {
+        let root = Fixture::new::<&str>(&[
+            ("sys/class/drm/card0/dev", "226:0\n"),
+            ("sys/class/drm/card0-DP-1/status", "disconnected\n"),
+            ("sys/class/drm/card0-eDP-1/status", "connected\n"),
+            ("sys/class/drm/card0-eDP-1/modes", "2256x1504\n"),
+            ("sys/class/drm/card1-HDMI-A-1/status", "connected\n"),
+            (
+                "sys/class/drm/card1-HDMI-A-1/modes",
+                "1920x1080\n1920x1200\n",
+            ),
+            ("sys/class/drm/card1-HDMI-A-2/status", "connected\n"),
+        ]);
+        root.write("sys/class/drm/card0-eDP-1/edid", blob(BOE));
+        root.write("sys/class/drm/card1-HDMI-A-1/edid", blob(DELL));
+
+        let info = DisplayInfo::from_root(&root);
+        let connectors: Vec<_> = info.monitors.iter().map(|m| m.connector.as_str()).collect();
+        assert_eq!(connectors, ["eDP-1", "HDMI-A-1", "HDMI-A-2"]);
+        // the preferred timing of the edid doesn't match the preferred mode of the connector
+        assert_eq!(info.monitors[1].refresh, None);
+
+        let lines = info.render(DynColors::Ansi(AnsiColors::Default));
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(
+            "BOE CQ NE135FBM-N41 (2256x1504 @ 60 Hz), DELL U2415 (1920x1080), HDMI-A-2"
+        ));
+
+        let full = DisplayInfo {
+            style: DisplayStyle::Full,
+            ..info
+        };
+        let lines = full.render(DynColors::Ansi(AnsiColors::Default));
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains("Display (HDMI-A-1)"));
+    }
+
+    #[test]
+    fn headless() {
+        let info = DisplayInfo::from_root(&Fixture::new::<&str>(&[]));
+        assert!(info.render(DynColors::Ansi(AnsiColors::Default)).is_empty());
+    }
+}
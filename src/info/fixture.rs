@@ -0,0 +1,51 @@
+use std::{
+    env,
+    fs::{create_dir_all, remove_dir_all, write},
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Temporary directory tree standing in for `/` in tests, removed when dropped
+pub struct Fixture(PathBuf);
+
+impl Fixture {
+    /// Create a tree from `(path, contents)` pairs, paths are relative to the root
+    pub fn new<C: AsRef<[u8]>>(files: &[(&str, C)]) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let root = env::temp_dir().join(format!(
+            "punfetch-fixture-{}-{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        create_dir_all(&root).unwrap();
+
+        let fixture = Self(root);
+        for (path, contents) in files {
+            fixture.write(path, contents);
+        }
+        fixture
+    }
+
+    /// Write a file, creating its parent directories
+    pub fn write<C: AsRef<[u8]>>(&self, path: &str, contents: C) {
+        let path = self.0.join(path);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, contents).unwrap();
+    }
+}
+
+impl Deref for Fixture {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
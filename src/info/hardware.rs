@@ -0,0 +1,192 @@
+use std::{fs::read_to_string, path::Path};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Values vendors leave in unset DMI fields, compared case insensitively
+const PLACEHOLDERS: [&str; 16] = [
+    "to be filled by o.e.m.",
+    "to be filled by oem",
+    "o.e.m.",
+    "oem",
+    "default string",
+    "system product name",
+    "system version",
+    "system manufacturer",
+    "base board product name",
+    "not applicable",
+    "not specified",
+    "none",
+    "unknown",
+    "invalid",
+    "type1productconfigid",
+    "0123456789",
+];
+
+/// Lines of [`HardwareInfo`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum HardwareLine {
+    /// Product name and version
+    Host,
+    /// Motherboard vendor and name
+    Board,
+    /// Firmware vendor, version and date
+    Firmware,
+}
+
+/// Host model, motherboard and firmware, from DMI or the device tree
+#[derive(Clone, Debug, Default, PartialEq, Eq, Render)]
+pub struct HardwareInfo {
+    pub host: Option<String>,
+    pub board: Option<String>,
+    pub firmware: Option<String>,
+}
+
+/// Trimmed contents of a DMI or device tree file, unless it is a placeholder
+fn value(path: &Path) -> Option<String> {
+    let value = read_to_string(path).ok()?;
+    let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    (!value.is_empty() && !PLACEHOLDERS.contains(&value.to_lowercase().as_str()))
+        .then(|| value.to_string())
+}
+
+/// Join the present parts with spaces, `None` when all are missing
+fn join<const N: usize>(parts: [Option<String>; N]) -> Option<String> {
+    let parts: Vec<String> = parts.into_iter().flatten().collect();
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+impl HardwareInfo {
+    /// Read DMI from `/sys/devices/virtual/dmi/id`, or the device tree model on boards without
+    /// it, under the given root
+    pub fn from_root(root: &Path) -> Self {
+        let dmi = |file: &str| value(&root.join("sys/devices/virtual/dmi/id").join(file));
+
+        let (name, version) = (dmi("product_name"), dmi("product_version"));
+        let host = match (dmi("sys_vendor"), name, version) {
+            // lenovo puts the machine type in the name, and the model in the version
+            (Some(vendor), _, Some(version)) if vendor.eq_ignore_ascii_case("lenovo") => {
+                Some(version)
+            }
+            (_, Some(name), Some(version)) if !name.contains(&version) => {
+                Some(format!("{name} {version}"))
+            }
+            (_, name, version) => name.or(version),
+        }
+        .or_else(|| value(&root.join("proc/device-tree/model")))
+        .or_else(|| value(&root.join("sys/firmware/devicetree/base/model")));
+
+        let firmware = join([dmi("bios_vendor"), dmi("bios_version")]).map(|firmware| {
+            match dmi("bios_date") {
+                Some(date) => format!("{firmware} ({date})"),
+                None => firmware,
+            }
+        });
+
+        Self {
+            host,
+            board: join([dmi("board_vendor"), dmi("board_name")]),
+            firmware,
+        }
+    }
+
+    /// Keep only the given lines
+    pub fn only(self, lines: &[HardwareLine]) -> Self {
+        Self {
+            host: self.host.filter(|_| lines.contains(&HardwareLine::Host)),
+            board: self.board.filter(|_| lines.contains(&HardwareLine::Board)),
+            firmware: self
+                .firmware
+                .filter(|_| lines.contains(&HardwareLine::Firmware)),
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for HardwareInfo {
+    const ID: &'static str = "hardware";
+    const TTL: Option<Duration> = Some(Duration::from_secs(7 * 24 * 60 * 60));
+
+    fn cache_hint() -> String {
+        boot_hint()
+    }
+
+    fn from_system(_: &System) -> Self {
+        Self::from_root(Path::new("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::fixture::Fixture, *};
+
+    /// Fixture with the given files in the DMI directory
+    fn dmi(files: &[(&str, &str)]) -> Fixture {
+        let files: Vec<_> = files
+            .iter()
+            .map(|(file, contents)| (format!("sys/devices/virtual/dmi/id/{file}"), *contents))
+            .collect();
+        let files: Vec<_> = files.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+        Fixture::new(&files)
+    }
+
+    #[test]
+    fn thinkpad() {
+        let info = HardwareInfo::from_root(&dmi(&[
+            ("sys_vendor", "LENOVO\n"),
+            ("product_name", "21HMCTO1WW\n"),
+            ("product_version", "ThinkPad X1 Carbon Gen 11\n"),
+            ("board_vendor", "LENOVO\n"),
+            ("board_name", "21HMCTO1WW\n"),
+            ("bios_vendor", "LENOVO\n"),
+            ("bios_version", "N3XET53W (1.28 )\n"),
+            ("bios_date", "08/10/2023\n"),
+        ]));
+        assert_eq!(
+            info,
+            HardwareInfo {
+                host: Some("ThinkPad X1 Carbon Gen 11".to_string()),
+                board: Some("LENOVO 21HMCTO1WW".to_string()),
+                firmware: Some("LENOVO N3XET53W (1.28 ) (08/10/2023)".to_string()),
+            }
+        );
+        assert_eq!(
+            info.only(&[HardwareLine::Board]),
+            HardwareInfo {
+                board: Some("LENOVO 21HMCTO1WW".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn placeholders() {
+        let info = HardwareInfo::from_root(&dmi(&[
+            ("sys_vendor", "System manufacturer\n"),
+            ("product_name", "System Product Name\n"),
+            ("product_version", "To Be Filled By O.E.M.\n"),
+            ("board_vendor", "ASUSTeK COMPUTER INC.\n"),
+            ("board_name", "ROG STRIX B550-F GAMING\n"),
+        ]));
+        assert_eq!(info.host, None);
+        assert_eq!(
+            info.board.as_deref(),
+            Some("ASUSTeK COMPUTER INC. ROG STRIX B550-F GAMING")
+        );
+        assert_eq!(info.firmware, None);
+    }
+
+    #[test]
+    fn device_tree() {
+        let root = Fixture::new(&[("proc/device-tree/model", "Raspberry Pi 4 Model B Rev 1.4\0")]);
+        assert_eq!(
+            HardwareInfo::from_root(&root).host.as_deref(),
+            Some("Raspberry Pi 4 Model B Rev 1.4")
+        );
+    }
+}
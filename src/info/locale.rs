@@ -0,0 +1,477 @@
+use std::{
+    fs::{read, read_link, read_to_string},
+    path::Path,
+};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Categories that can override `LANG`
+const LC_CATEGORIES: [&str; 12] = [
+    "LC_CTYPE",
+    "LC_NUMERIC",
+    "LC_TIME",
+    "LC_COLLATE",
+    "LC_MONETARY",
+    "LC_MESSAGES",
+    "LC_PAPER",
+    "LC_NAME",
+    "LC_ADDRESS",
+    "LC_TELEPHONE",
+    "LC_MEASUREMENT",
+    "LC_IDENTIFICATION",
+];
+
+/// Locale, system timezone and keyboard layout
+#[derive(Clone, Debug, Default, PartialEq, Eq, Render)]
+pub struct LocaleInfo {
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub keyboard: Option<String>,
+}
+
+impl LocaleInfo {
+    /// Read the locale from the environment, falling back to the system locale, and the timezone
+    /// and keyboard layout from `/etc` under the given root, with the utc offset at `now` in unix
+    /// seconds
+    pub fn from_root(root: &Path, var: impl Fn(&str) -> Option<String>, now: i64) -> Self {
+        let mut locale = effective_locale(|name| var(name).filter(|v| !v.is_empty()));
+        for file in ["etc/locale.conf", "etc/default/locale"] {
+            let contents = read_to_string(root.join(file)).unwrap_or_default();
+            locale = locale.or_else(|| effective_locale(|name| ini_value(&contents, "", name)));
+        }
+
+        Self {
+            locale,
+            timezone: timezone(root, now),
+            keyboard: keyboard(root),
+        }
+    }
+}
+
+/// `LC_ALL`, or `LANG` followed by the categories overriding it
+fn effective_locale(get: impl Fn(&str) -> Option<String>) -> Option<String> {
+    if let Some(all) = get("LC_ALL") {
+        return Some(all);
+    }
+    let lang = get("LANG");
+    let overrides: Vec<String> = LC_CATEGORIES
+        .iter()
+        .filter_map(|name| {
+            let value = get(name)?;
+            (Some(&value) != lang.as_ref()).then(|| format!("{name}={value}"))
+        })
+        .collect();
+    match (lang, overrides.is_empty()) {
+        (Some(lang), true) => Some(lang),
+        (Some(lang), false) => Some(format!("{lang} ({})", overrides.join(", "))),
+        (None, false) => Some(overrides.join(", ")),
+        (None, true) => None,
+    }
+}
+
+/// Name of the system timezone and its current offset, e.g. `Europe/Berlin (UTC+02:00)`
+fn timezone(root: &Path, now: i64) -> Option<String> {
+    // `/etc/localtime` links into the zoneinfo database, `/etc/timezone` is debian's
+    let name = read_link(root.join("etc/localtime"))
+        .ok()
+        .and_then(|target| {
+            let target = target.to_string_lossy().to_string();
+            let (_, name) = target.split_once("zoneinfo/")?;
+            Some(name.to_string())
+        })
+        .or_else(|| {
+            let name = read_to_string(root.join("etc/timezone")).ok()?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| name.to_string())
+        });
+
+    let tzif = read(root.join("etc/localtime")).ok().or_else(|| {
+        let name = name.as_ref()?;
+        read(root.join("usr/share/zoneinfo").join(name)).ok()
+    });
+    let offset = tzif.and_then(|tzif| utc_offset(&tzif, now)).map(|offset| {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let minutes = offset.abs() / 60;
+        format!("UTC{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+    });
+
+    match (name, offset) {
+        (Some(name), Some(offset)) => Some(format!("{name} ({offset})")),
+        (name, offset) => name.or(offset),
+    }
+}
+
+/// Offset from utc in seconds at the given unix time, from a TZif file
+fn utc_offset(tzif: &[u8], now: i64) -> Option<i64> {
+    let counts = |header: &[u8]| -> Option<[usize; 6]> {
+        if header.get(..4)? != b"TZif" {
+            return None;
+        }
+        let mut counts = [0; 6];
+        for (i, count) in counts.iter_mut().enumerate() {
+            let bytes = header.get(20 + i * 4..24 + i * 4)?;
+            *count = u32::from_be_bytes(bytes.try_into().ok()?) as usize;
+        }
+        Some(counts)
+    };
+    // version 2 and later repeat the data with 64 bit times after the 32 bit one, and end with a
+    // rule for times past the last transition
+    let [isut, isstd, leap, time, types, chars] = counts(tzif)?;
+    let (data, size) = match tzif[4] {
+        b'2'.. => {
+            let v1 = 44 + time * 5 + types * 6 + chars + leap * 8 + isstd + isut;
+            (tzif.get(v1..)?, 8)
+        }
+        _ => (tzif, 4),
+    };
+    let [isut, isstd, leap, time, types, chars] = counts(data)?;
+
+    let body = data.get(44..)?;
+    let transitions: Vec<i64> = body
+        .get(..time * size)?
+        .chunks(size)
+        .map(|t| match size {
+            8 => i64::from_be_bytes(t.try_into().unwrap_or_default()),
+            _ => i32::from_be_bytes(t.try_into().unwrap_or_default()) as i64,
+        })
+        .collect();
+    let indices = body.get(time * size..time * (size + 1))?;
+    let infos = body.get(time * (size + 1)..time * (size + 1) + types * 6)?;
+    let offset = |index: u8| -> Option<i64> {
+        let info = infos.get(index as usize * 6..index as usize * 6 + 4)?;
+        Some(i32::from_be_bytes(info.try_into().ok()?) as i64)
+    };
+
+    let footer = if size == 8 {
+        let end = time * (size + 1) + types * 6 + chars + leap * (size + 4) + isstd + isut;
+        let footer = String::from_utf8_lossy(body.get(end..).unwrap_or_default()).to_string();
+        footer.trim_matches('\n').to_string()
+    } else {
+        String::new()
+    };
+
+    match transitions.iter().rposition(|&t| t <= now) {
+        // before the first transition the first type applies
+        None if !transitions.is_empty() => offset(0),
+        Some(i) if i + 1 < transitions.len() => offset(indices[i]),
+        _ if !footer.is_empty() => posix_offset(&footer, now),
+        Some(i) => offset(indices[i]),
+        None => offset(0),
+    }
+}
+
+/// Offset from utc in seconds at the given unix time, from a POSIX TZ rule like
+/// `CET-1CEST,M3.5.0,M10.5.0/3`
+fn posix_offset(rule: &str, now: i64) -> Option<i64> {
+    let mut rest = skip_name(rule)?;
+    let (std_offset, len) = duration(rest)?;
+    // posix offsets are west of utc
+    let std = -std_offset;
+    rest = &rest[len..];
+    if rest.is_empty() {
+        return Some(std);
+    }
+
+    rest = skip_name(rest)?;
+    let dst = match duration(rest) {
+        Some((offset, len)) => {
+            rest = &rest[len..];
+            -offset
+        }
+        None => std + 3600,
+    };
+    let mut dates = rest.strip_prefix(',')?.split(',');
+    let (start, end) = (dates.next()?, dates.next()?);
+
+    let year = civil_from_days((now + std).div_euclid(86400)).0;
+    // transitions happen at local time, the start one in standard time and the end one in dst
+    let start = transition(start, year)? - std;
+    let end = transition(end, year)? - dst;
+    let in_dst = if start < end {
+        start <= now && now < end
+    } else {
+        // the southern hemisphere, with dst over the new year
+        !(end <= now && now < start)
+    };
+    Some(if in_dst { dst } else { std })
+}
+
+/// Text after a zone abbreviation, which is either alphabetic or quoted in `<>`
+fn skip_name(text: &str) -> Option<&str> {
+    match text.strip_prefix('<') {
+        Some(quoted) => Some(&quoted[quoted.find('>')? + 1..]),
+        None => Some(text.trim_start_matches(|c: char| c.is_ascii_alphabetic())),
+    }
+}
+
+/// Signed `[+-]hh[:mm[:ss]]` in seconds, and the length of text it took
+fn duration(text: &str) -> Option<(i64, usize)> {
+    let len = text
+        .find(|c: char| !(c.is_ascii_digit() || c == ':' || c == '+' || c == '-'))
+        .unwrap_or(text.len());
+    let (sign, digits) = match text[..len].strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, text[..len].trim_start_matches('+')),
+    };
+    let mut seconds = 0;
+    for (part, unit) in digits.split(':').zip([3600, 60, 1]) {
+        seconds += part.parse::<i64>().ok()? * unit;
+    }
+    Some((sign * seconds, len))
+}
+
+/// Local time in seconds since the epoch of a `Mm.w.d`, `Jn` or `n` date with an optional `/time`
+/// in the given year
+fn transition(date: &str, year: i64) -> Option<i64> {
+    let (date, time) = match date.split_once('/') {
+        Some((date, time)) => (date, duration(time)?.0),
+        None => (date, 2 * 3600),
+    };
+    let first = days_from_civil(year, 1, 1);
+    let day = if let Some(date) = date.strip_prefix('M') {
+        month_week_day(date, year)?
+    } else if let Some(day) = date.strip_prefix('J') {
+        // one-based day of the year that never counts february 29
+        let day = day.parse::<i64>().ok()?;
+        let leap = days_from_civil(year, 3, 1) - days_from_civil(year, 2, 1) == 29;
+        first + day - 1 + i64::from(leap && day >= 60)
+    } else {
+        // zero-based day of the year, february 29 included
+        first + date.parse::<i64>().ok()?
+    };
+    Some(day * 86400 + time)
+}
+
+/// Days since 1970-01-01 of the `m.w.d` date, the weekday `d` of week `w` in month `m`
+fn month_week_day(date: &str, year: i64) -> Option<i64> {
+    let mut fields = date.split('.');
+    let mut field = || fields.next()?.parse::<i64>().ok();
+    let (month, week, weekday) = (field()?, field()?, field()?);
+
+    let first = days_from_civil(year, month, 1);
+    // 1970-01-01 was a thursday
+    let first_weekday = (first + 4).rem_euclid(7);
+    let mut day = first + (weekday - first_weekday).rem_euclid(7) + (week - 1) * 7;
+    // week 5 means the last one, which may be the fourth
+    let next_month = match month {
+        12 => days_from_civil(year + 1, 1, 1),
+        _ => days_from_civil(year, month + 1, 1),
+    };
+    while day >= next_month {
+        day -= 7;
+    }
+    Some(day)
+}
+
+/// Days since 1970-01-01 of a proleptic gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Year, month and day of a number of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Keyboard layouts with their variants, e.g. `us, de (nodeadkeys)`, or the console keymap
+fn keyboard(root: &Path) -> Option<String> {
+    ["etc/vconsole.conf", "etc/default/keyboard"]
+        .iter()
+        .find_map(|file| {
+            let contents = read_to_string(root.join(file)).ok()?;
+            let Some(layouts) = ini_value(&contents, "", "XKBLAYOUT") else {
+                return ini_value(&contents, "", "KEYMAP");
+            };
+            let variants = ini_value(&contents, "", "XKBVARIANT").unwrap_or_default();
+            let mut variants = variants.split(',');
+            let layouts: Vec<String> = layouts
+                .split(',')
+                .map(|layout| match variants.next().map(str::trim) {
+                    Some(variant) if !variant.is_empty() => format!("{layout} ({variant})"),
+                    _ => layout.to_string(),
+                })
+                .collect();
+            Some(layouts.join(", "))
+        })
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for LocaleInfo {
+    const ID: &'static str = "locale";
+
+    fn from_system(_: &System) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        Self::from_root(Path::new("/"), |var| env::var(var).ok(), now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::{super::fixture::Fixture, *};
+
+    /// 2024-07-01 and 2024-01-15, at noon utc
+    const SUMMER: i64 = 1719835200;
+    const WINTER: i64 = 1705320000;
+
+    /// Version 2 TZif with an empty 32 bit block, the given transitions and types, and a footer
+    fn tzif(transitions: &[(i64, u8)], offsets: &[i32], footer: &str) -> Vec<u8> {
+        let header = |time: usize, types: usize, chars: usize| {
+            let mut header = b"TZif2".to_vec();
+            header.extend([0; 15]);
+            for count in [0, 0, 0, time, types, chars] {
+                header.extend((count as u32).to_be_bytes());
+            }
+            header
+        };
+        let mut data = header(0, 0, 0);
+        data.extend(header(transitions.len(), offsets.len(), 4));
+        for (time, _) in transitions {
+            data.extend(time.to_be_bytes());
+        }
+        data.extend(transitions.iter().map(|(_, index)| index));
+        for (i, offset) in offsets.iter().enumerate() {
+            data.extend(offset.to_be_bytes());
+            data.extend([u8::from(i > 0), 0]);
+        }
+        data.extend(b"UTC\0");
+        data.extend(format!("\n{footer}\n").bytes());
+        data
+    }
+
+    #[test]
+    fn posix_rules() {
+        let berlin = "CET-1CEST,M3.5.0,M10.5.0/3";
+        assert_eq!(posix_offset(berlin, SUMMER), Some(7200));
+        assert_eq!(posix_offset(berlin, WINTER), Some(3600));
+        // 2024-03-31 00:59:59 and 01:00:00 utc
+        assert_eq!(posix_offset(berlin, 1711846799), Some(3600));
+        assert_eq!(posix_offset(berlin, 1711846800), Some(7200));
+
+        let sydney = "AEST-10AEDT,M10.1.0,M4.1.0/3";
+        assert_eq!(posix_offset(sydney, SUMMER), Some(36000));
+        assert_eq!(posix_offset(sydney, WINTER), Some(39600));
+
+        assert_eq!(posix_offset("<+0530>-5:30", SUMMER), Some(19800));
+        assert_eq!(posix_offset("EST5EDT,M3.2.0,M11.1.0", WINTER), Some(-18000));
+
+        // J60 is march 1 even in leap years, 2024-03-01 07:00:00 utc
+        let julian = "EST5EDT,J60,J300";
+        assert_eq!(posix_offset(julian, 1709276399), Some(-18000));
+        assert_eq!(posix_offset(julian, 1709276400), Some(-14400));
+        assert_eq!(posix_offset(julian, SUMMER), Some(-14400));
+        assert_eq!(posix_offset(julian, WINTER), Some(-18000));
+
+        // day 59 counts february 29, 2024-02-29 07:00:00 utc
+        let zero_based = "EST5EDT,59,299";
+        assert_eq!(posix_offset(zero_based, 1709189999), Some(-18000));
+        assert_eq!(posix_offset(zero_based, 1709190000), Some(-14400));
+        assert_eq!(posix_offset(zero_based, SUMMER), Some(-14400));
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+    }
+
+    #[test]
+    fn tzif_transitions() {
+        // a single transition to dst in 2023, then the footer rule
+        let data = tzif(
+            &[(1679792400, 1)],
+            &[3600, 7200],
+            "CET-1CEST,M3.5.0,M10.5.0/3",
+        );
+        assert_eq!(utc_offset(&data, WINTER), Some(3600));
+        assert_eq!(utc_offset(&data, SUMMER), Some(7200));
+        assert_eq!(utc_offset(&data, 1679792399), Some(3600));
+
+        let fixed = tzif(&[], &[-10800], "");
+        assert_eq!(utc_offset(&fixed, SUMMER), Some(-10800));
+        assert_eq!(utc_offset(b"not a tzif file", SUMMER), None);
+    }
+
+    #[test]
+    fn system_files() {
+        let root = Fixture::new(&[
+            (
+                "etc/locale.conf",
+                "LANG=en_US.UTF-8\nLC_TIME=de_DE.UTF-8\nLC_PAPER=en_US.UTF-8\n",
+            ),
+            (
+                "etc/vconsole.conf",
+                "KEYMAP=de-latin1\nXKBLAYOUT=us,de\nXKBVARIANT=,nodeadkeys\n",
+            ),
+        ]);
+        root.write(
+            "usr/share/zoneinfo/Europe/Berlin",
+            tzif(&[], &[3600, 7200], "CET-1CEST,M3.5.0,M10.5.0/3"),
+        );
+        symlink(
+            "../usr/share/zoneinfo/Europe/Berlin",
+            root.join("etc/localtime"),
+        )
+        .unwrap();
+
+        let info = LocaleInfo::from_root(&root, |_| None, SUMMER);
+        assert_eq!(
+            info,
+            LocaleInfo {
+                locale: Some("en_US.UTF-8 (LC_TIME=de_DE.UTF-8)".to_string()),
+                timezone: Some("Europe/Berlin (UTC+02:00)".to_string()),
+                keyboard: Some("us, de (nodeadkeys)".to_string()),
+            }
+        );
+
+        // the environment wins over the system locale
+        let info = LocaleInfo::from_root(
+            &root,
+            |var| (var == "LC_ALL").then(|| "C.UTF-8".to_string()),
+            WINTER,
+        );
+        assert_eq!(info.locale.as_deref(), Some("C.UTF-8"));
+        assert_eq!(info.timezone.as_deref(), Some("Europe/Berlin (UTC+01:00)"));
+    }
+
+    #[test]
+    fn debian_files() {
+        let root = Fixture::new(&[
+            ("etc/timezone", "America/Sao_Paulo\n"),
+            (
+                "etc/default/keyboard",
+                "XKBMODEL=\"pc105\"\nXKBLAYOUT=\"br\"\nXKBVARIANT=\"\"\n",
+            ),
+        ]);
+        root.write(
+            "usr/share/zoneinfo/America/Sao_Paulo",
+            tzif(&[], &[-10800], "<-03>3"),
+        );
+
+        let info = LocaleInfo::from_root(&root, |_| None, SUMMER);
+        assert_eq!(info.locale, None);
+        assert_eq!(
+            info.timezone.as_deref(),
+            Some("America/Sao_Paulo (UTC-03:00)")
+        );
+        assert_eq!(info.keyboard.as_deref(), Some("br"));
+    }
+}
@@ -0,0 +1,354 @@
+use std::{fs::read_to_string, path::Path};
+
+use byte_unit::Byte;
+use owo_colors::{colored::Color, DynColors};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::{RefreshKind, System, SystemExt};
+
+use super::*;
+
+/// Memory usage as a stacked bar of used, buffers/cache and free memory, with a second bar for
+/// swap devices when present
+pub struct MemoryInfo {
+    pub total: u64,
+    pub used: u64,
+    pub cache: u64,
+    pub free: u64,
+    /// Swap devices as `(name, size, used)`, zram devices include their compression ratio
+    pub swaps: Vec<(String, u64, u64)>,
+    /// Whether the memory is a cgroup limit rather than the host's
+    pub limited: bool,
+    pub style: BarStyle,
+    pub legend: Legend,
+    /// Thresholds for the used memory
+    pub thresholds: Thresholds,
+    /// Thresholds for the used swap
+    pub swap_thresholds: Thresholds,
+}
+
+impl MemoryInfo {
+    /// Read memory and swap usage from `/proc/meminfo` and `/proc/swaps` under the given root
+    pub fn from_root(root: &Path) -> Option<Self> {
+        let meminfo = read_to_string(root.join("proc/meminfo")).ok()?;
+        let field = |name: &str| -> Option<u64> {
+            let kib: u64 = meminfo
+                .lines()
+                .find_map(|l| l.strip_prefix(name)?.strip_prefix(':'))?
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()?;
+            Some(kib * 1024)
+        };
+
+        let total = field("MemTotal")?;
+        let free = field("MemFree")?;
+        // kernels older than 3.14 don't report an estimate, so approximate it like free(1) does
+        let available = field("MemAvailable").unwrap_or_else(|| {
+            ["Buffers", "Cached", "SReclaimable"]
+                .into_iter()
+                .filter_map(field)
+                .fold(free, |a, b| a + b)
+        });
+        let used = total.saturating_sub(available);
+        let cache = total.saturating_sub(used).saturating_sub(free);
+
+        let mut swaps = swaps(root);
+        if swaps.is_empty() {
+            if let (Some(size), Some(free)) = (field("SwapTotal"), field("SwapFree")) {
+                if size > 0 {
+                    swaps.push(("swap".to_string(), size, size.saturating_sub(free)));
+                }
+            }
+        }
+
+        Some(Self {
+            total,
+            used,
+            cache,
+            free,
+            swaps,
+            limited: false,
+            style: BarStyle::default(),
+            legend: Legend::default(),
+            thresholds: MEMORY_THRESHOLDS,
+            swap_thresholds: SWAP_THRESHOLDS,
+        })
+    }
+}
+
+impl MemoryInfo {
+    /// Show the cgroup memory limit in place of the host's memory, when it is lower
+    pub fn with_limits(self, limits: &CgroupLimits) -> Self {
+        match limits.effective_memory(self.total) {
+            Some(memory) => Self {
+                total: memory.limit,
+                used: memory.used(),
+                cache: memory.cache,
+                free: memory.limit.saturating_sub(memory.usage),
+                limited: true,
+                ..self
+            },
+            None => self,
+        }
+    }
+
+    /// Memory and, when there is any, swap usage against the thresholds
+    pub fn checks(&self) -> Vec<Check> {
+        let mut checks = vec![Check::new(
+            "memory",
+            self.used as f64 / self.total as f64,
+            self.thresholds,
+        )];
+        if !self.swaps.is_empty() {
+            let (size, used) = self
+                .swaps
+                .iter()
+                .fold((0, 0), |(size, used), (_, s, u)| (size + s, used + u));
+            checks.push(Check::new(
+                "swap",
+                used as f64 / size as f64,
+                self.swap_thresholds,
+            ));
+        }
+        checks
+    }
+}
+
+/// Active swap devices from `/proc/swaps`
+fn swaps(root: &Path) -> Vec<(String, u64, u64)> {
+    read_to_string(root.join("proc/swaps"))
+        .unwrap_or_default()
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            // Filename Type Size Used Priority, with sizes in KiB
+            let mut fields = line.split_whitespace();
+            let path = fields.next()?;
+            let size = fields.nth(1)?.parse::<u64>().ok()? * 1024;
+            let used = fields.next()?.parse::<u64>().ok()? * 1024;
+
+            let name = path.rsplit('/').next().unwrap_or(path).to_string();
+            let name = match zram_ratio(root, &name) {
+                Some(ratio) => format!("{name} {ratio:.1}x"),
+                None => name,
+            };
+            Some((name, size, used))
+        })
+        .collect()
+}
+
+/// Compression ratio of a zram device, from its `mm_stat`
+fn zram_ratio(root: &Path, name: &str) -> Option<f64> {
+    if !name.starts_with("zram") {
+        return None;
+    }
+
+    let stat = read_to_string(root.join("sys/block").join(name).join("mm_stat")).ok()?;
+    let mut fields = stat.split_whitespace().map(|f| f.parse::<f64>().ok());
+    let (original, compressed) = (fields.next()??, fields.next()??);
+    (compressed > 0.0).then(|| original / compressed)
+}
+
+fn bytes(value: u64) -> String {
+    Byte::from_bytes(value as u128)
+        .get_appropriate_unit(true)
+        .to_string()
+}
+
+impl Render for MemoryInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let total = self.total as f64;
+        let mut lines = PercentBar {
+            title: if self.limited {
+                "Memory (limit)".to_string()
+            } else {
+                "Memory".to_string()
+            },
+            total,
+            items: [
+                ("used", self.used),
+                ("cache", self.cache),
+                ("free", self.free),
+            ]
+            .into_iter()
+            .map(|(name, value)| {
+                let label = format!("{name} {}", bytes(value));
+                (label, value as f64, value as f64 / total)
+            })
+            .collect(),
+            // keep yellow and red free for the thresholds
+            colors: vec![
+                DynColors::Ansi(Color::Blue),
+                DynColors::Ansi(Color::Cyan),
+                DynColors::Ansi(Color::Green),
+            ],
+            width: DEFAULT_BAR_WIDTH,
+            style: self.style,
+            legend: self.legend,
+            thresholds: vec![Some(self.thresholds)],
+        }
+        .render(color);
+
+        if !self.swaps.is_empty() {
+            lines.extend(
+                PercentBar {
+                    title: "Swap".to_string(),
+                    total: self.swaps.iter().map(|(_, size, _)| *size as f64).sum(),
+                    items: self
+                        .swaps
+                        .iter()
+                        .map(|(name, size, used)| {
+                            let used = *used as f64;
+                            (
+                                format!("{name} {}", bytes(*size)),
+                                used,
+                                used / *size as f64,
+                            )
+                        })
+                        .collect(),
+                    colors: DEFAULT_COLORS.to_vec(),
+                    width: DEFAULT_BAR_WIDTH,
+                    style: self.style,
+                    legend: self.legend,
+                    thresholds: vec![Some(self.swap_thresholds); self.swaps.len()],
+                }
+                .render(color),
+            );
+        }
+
+        lines
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for MemoryInfo {
+    const ID: &'static str = "memory";
+
+    fn refresh_kind() -> RefreshKind {
+        RefreshKind::new().with_memory()
+    }
+
+    fn from_system(sys: &System) -> Self {
+        let limits = CgroupLimits::from_root(Path::new("/"));
+        Self::from_root(Path::new("/"))
+            .unwrap_or_else(|| {
+                let (total, used, free) =
+                    (sys.total_memory(), sys.used_memory(), sys.free_memory());
+                let swap = sys.total_swap();
+                Self {
+                    total,
+                    used,
+                    cache: total.saturating_sub(used).saturating_sub(free),
+                    free,
+                    swaps: (swap > 0)
+                        .then(|| ("swap".to_string(), swap, sys.used_swap()))
+                        .into_iter()
+                        .collect(),
+                    limited: false,
+                    style: BarStyle::default(),
+                    legend: Legend::default(),
+                    thresholds: MEMORY_THRESHOLDS,
+                    swap_thresholds: SWAP_THRESHOLDS,
+                }
+            })
+            .with_limits(&limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    const MEMINFO: &str = "MemTotal:       16000000 kB
+MemFree:         4000000 kB
+MemAvailable:   10000000 kB
+Buffers:          500000 kB
+Cached:          5000000 kB
+SwapTotal:       8000000 kB
+SwapFree:        6000000 kB
+";
+
+    #[test]
+    fn meminfo() {
+        let root = Fixture::new(&[
+            ("proc/meminfo", MEMINFO),
+            (
+                "proc/swaps",
+                "Filename\tType\t\tSize\t\tUsed\t\tPriority
+/dev/zram0                              partition\t4000000\t\t1000000\t\t100
+/swapfile                               file\t\t4000000\t\t1000000\t\t-2
+",
+            ),
+            (
+                "sys/block/zram0/mm_stat",
+                "1024000000 256000000 260000000 0 0 0 0 0 0",
+            ),
+        ]);
+
+        let memory = MemoryInfo::from_root(&root).unwrap();
+        assert_eq!(memory.total, 16000000 * 1024);
+        assert_eq!(memory.used, 6000000 * 1024);
+        assert_eq!(memory.cache, 6000000 * 1024);
+        assert_eq!(memory.free, 4000000 * 1024);
+        assert_eq!(
+            memory.swaps,
+            [
+                ("zram0 4.0x".to_string(), 4000000 * 1024, 1000000 * 1024),
+                ("swapfile".to_string(), 4000000 * 1024, 1000000 * 1024),
+            ]
+        );
+
+        let lines = memory.render(DynColors::Ansi(AnsiColors::Default));
+        assert!(lines.iter().any(|l| l.contains("Swap")));
+
+        let checks = memory.checks();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[1].value, 0.25);
+        assert_eq!(checks[1].thresholds, SWAP_THRESHOLDS);
+    }
+
+    #[test]
+    fn without_swaps_file() {
+        let root = Fixture::new(&[("proc/meminfo", MEMINFO.replace("MemAvailable", "Ignored"))]);
+
+        let memory = MemoryInfo::from_root(&root).unwrap();
+        assert_eq!(memory.used, 6500000 * 1024);
+        assert_eq!(
+            memory.swaps,
+            [("swap".to_string(), 8000000 * 1024, 2000000 * 1024)]
+        );
+    }
+
+    #[test]
+    fn cgroup_limit() {
+        let root = Fixture::new(&[("proc/meminfo", MEMINFO)]);
+        let memory = MemoryInfo::from_root(&root).unwrap();
+        let limits = CgroupLimits {
+            memory: Some(MemoryLimit {
+                limit: 4 << 30,
+                usage: 3 << 30,
+                cache: 1 << 30,
+            }),
+            ..Default::default()
+        };
+
+        let limited = memory.with_limits(&limits);
+        assert!(limited.limited);
+        assert_eq!(
+            (limited.total, limited.used, limited.cache, limited.free),
+            (4 << 30, 2 << 30, 1 << 30, 1 << 30)
+        );
+        let lines = limited.render(DynColors::Ansi(AnsiColors::Default));
+        assert!(lines[0].contains("Memory (limit)"));
+    }
+
+    #[test]
+    fn missing_meminfo() {
+        let root = Fixture::new::<&str>(&[]);
+        assert!(MemoryInfo::from_root(&root).is_none());
+    }
+}
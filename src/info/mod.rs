@@ -0,0 +1,678 @@
+use std::{
+    default::Default,
+    env,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    string::ToString,
+    time::Duration,
+};
+
+use byte_unit::Byte;
+use humantime::format_duration;
+use owo_colors::{colored::Color, DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::{CpuExt, CpuRefreshKind, RefreshKind, System, SystemExt};
+
+use super::*;
+#[cfg(feature = "sysinfo")]
+use crate::cache::boot_hint;
+
+pub use bar::*;
+pub use battery::*;
+pub use boot::*;
+pub use cgroup::*;
+pub use cpu::*;
+pub use cpu_usage::*;
+pub use disk::*;
+pub use display::*;
+pub use hardware::*;
+pub use locale::*;
+pub use memory::*;
+pub use network::*;
+pub use security::*;
+pub use sensors::*;
+pub use terminal_font::*;
+pub use theme::*;
+pub use threshold::*;
+pub use virtualization::*;
+
+mod bar;
+mod battery;
+mod boot;
+mod cgroup;
+mod cpu;
+mod cpu_usage;
+mod disk;
+mod display;
+#[cfg(test)]
+mod fixture;
+mod hardware;
+mod locale;
+mod memory;
+mod network;
+mod security;
+mod sensors;
+mod terminal_font;
+mod theme;
+mod threshold;
+mod virtualization;
+
+pub const DEFAULT_BAR_WIDTH: usize = 30;
+pub const DEFAULT_COLORS: [DynColors; 7] = [
+    DynColors::Ansi(Color::Red),
+    DynColors::Ansi(Color::Green),
+    DynColors::Ansi(Color::Yellow),
+    DynColors::Ansi(Color::Blue),
+    DynColors::Ansi(Color::Magenta),
+    DynColors::Ansi(Color::Cyan),
+    DynColors::Ansi(Color::White),
+];
+
+#[cfg(feature = "sysinfo")]
+/// Info built from a shared [`System`], declaring which parts of it need to be refreshed
+pub trait Module: Render + Send + Sized + 'static {
+    /// Unique module identifier
+    const ID: &'static str;
+
+    /// Subsystems that must be refreshed in the [`System`] passed to [`Module::from_system`]
+    fn refresh_kind() -> RefreshKind {
+        RefreshKind::new()
+    }
+
+    /// How long the rendered module may be served from the [`Cache`], `None` to never cache it
+    const TTL: Option<Duration> = None;
+
+    /// Data that invalidates cached renders when it changes, e.g. the kernel version
+    fn cache_hint() -> String {
+        String::new()
+    }
+
+    /// Build the module from the shared system
+    fn from_system(sys: &System) -> Self;
+}
+
+/// Generic color bar
+pub struct ColorBar(pub Vec<DynColors>);
+
+impl Default for ColorBar {
+    fn default() -> Self {
+        let mut colors = vec![DynColors::Ansi(Color::Black)];
+        colors.extend(DEFAULT_COLORS);
+        Self(colors)
+    }
+}
+
+impl ColorBar {
+    /// A cell of the given text per color, using the color as background
+    pub fn strip(&self, cell: &str) -> String {
+        let mut buf = String::new();
+        for color in &self.0 {
+            buf.push_str(&format!("{}", cell.on_color(*color)));
+        }
+        buf
+    }
+}
+
+impl Render for ColorBar {
+    fn render(&self, _: DynColors) -> Vec<String> {
+        vec![String::new(), self.strip("   ")]
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for ColorBar {
+    const ID: &'static str = "colors";
+
+    fn from_system(_: &System) -> Self {
+        Self::default()
+    }
+}
+
+/// Match text against a glob pattern, where `*` matches any run of characters and `?` any one
+fn glob(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<_>, Vec<_>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    // position of the last `*` and the text position it was tried at, for backtracking
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    p = sp + 1;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Value of a key in an ini style file, within the given section, or before any section when it
+/// is empty, with surrounding quotes removed
+fn ini_value(contents: &str, section: &str, key: &str) -> Option<String> {
+    let mut current = "";
+    contents.lines().map(str::trim).find_map(|line| {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = name;
+            return None;
+        }
+        let (k, value) = line.split_once('=')?;
+        let value = value.trim().trim_matches('"');
+        (current == section && k.trim() == key && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+#[cfg(feature = "sysinfo")]
+/// Home directory and `$XDG_CONFIG_HOME`, which defaults to `~/.config`
+fn home_dirs() -> Option<(PathBuf, PathBuf)> {
+    let home = PathBuf::from(env::var_os("HOME")?);
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home.join(".config"));
+    Some((home, config))
+}
+
+#[cfg(feature = "sysinfo")]
+/// User and host, with a horizontal line underneath
+pub struct UserInfo {
+    pub user: String,
+    pub host: String,
+}
+
+#[cfg(feature = "sysinfo")]
+impl UserInfo {
+    pub fn new(sys: &System) -> Self {
+        let user = effective_uid(Path::new("/"))
+            .and_then(|uid| passwd_name(Path::new("/"), uid).or_else(|| getent_name(uid)))
+            .unwrap_or_else(|| env::var("USER").unwrap_or_else(|_| "unknown".into()));
+        let host = sys.host_name().unwrap_or_else(|| "localhost".to_string());
+
+        Self { user, host }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for UserInfo {
+    const ID: &'static str = "user";
+
+    fn from_system(sys: &System) -> Self {
+        Self::new(sys)
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+/// Effective uid of the current process, from `/proc/self/status`
+fn effective_uid(root: &Path) -> Option<u32> {
+    read_to_string(root.join("proc/self/status"))
+        .ok()?
+        .lines()
+        .find_map(|l| l.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+#[cfg(feature = "sysinfo")]
+/// Name of the user with the given uid, from `/etc/passwd`
+fn passwd_name(root: &Path, uid: u32) -> Option<String> {
+    read_to_string(root.join("etc/passwd"))
+        .ok()?
+        .lines()
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            (fields.nth(1)?.parse::<u32>().ok()? == uid).then(|| name.to_string())
+        })
+}
+
+#[cfg(feature = "sysinfo")]
+/// Name of the user with the given uid from NSS, for LDAP and sssd users missing from `/etc/passwd`
+fn getent_name(uid: u32) -> Option<String> {
+    let output = std::process::Command::new("getent")
+        .args(["passwd", &uid.to_string()])
+        .output()
+        .ok()?;
+    let line = String::from_utf8(output.stdout).ok()?;
+    let name = line.split(':').next()?.trim();
+    (output.status.success() && !name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(feature = "sysinfo")]
+impl Render for UserInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let len = self.user.len() + self.host.len() + 3;
+        vec![
+            format!(
+                "{} ~ {}",
+                self.user.bold().color(color),
+                self.host.bold().color(color)
+            ),
+            format!("{}", "-".repeat(len).color(Color::Default)),
+        ]
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+/// Distro, kernel, and shell
+#[derive(Render)]
+pub struct HostInfo {
+    pub distro: String,
+    pub kernel: Option<String>,
+    pub terminal: Option<String>,
+}
+
+#[cfg(feature = "sysinfo")]
+impl HostInfo {
+    pub fn new(sys: &System) -> Self {
+        Self {
+            distro: sys.name().unwrap_or_else(|| sys.distribution_id()),
+            kernel: sys.kernel_version(),
+            terminal: env::var("TERM").ok(),
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for HostInfo {
+    const ID: &'static str = "host";
+
+    fn from_system(sys: &System) -> Self {
+        Self::new(sys)
+    }
+}
+
+/// How load averages are shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum LoadFormat {
+    /// Raw 1, 5 and 15 minute averages, e.g. `0.52, 0.48, 0.40`
+    #[default]
+    Raw,
+    /// Averages as a percentage of the logical cpu count, e.g. `13 %, 12 %, 10 %`
+    Percent,
+}
+
+/// 1, 5 and 15 minute load averages, each highlighted by its load per cpu, where `cpus` may be a
+/// fractional cgroup quota
+pub fn format_load(
+    load: [f64; 3],
+    cpus: f64,
+    format: LoadFormat,
+    thresholds: Thresholds,
+) -> String {
+    load.iter()
+        .map(|&load| {
+            let text = match format {
+                LoadFormat::Raw => format!("{load:.2}"),
+                LoadFormat::Percent => format!("{:.0} %", load / cpus * 100.0),
+            };
+            thresholds.paint(load / cpus, text)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(feature = "sysinfo")]
+/// Display options for [`SystemInfo`]
+#[derive(Clone, Copy, Debug)]
+pub struct SystemOptions {
+    pub load_format: LoadFormat,
+    /// Memory usage at which it is highlighted
+    pub memory_thresholds: Thresholds,
+    /// Load per logical cpu at which it is highlighted
+    pub load_thresholds: Thresholds,
+}
+
+#[cfg(feature = "sysinfo")]
+impl Default for SystemOptions {
+    fn default() -> Self {
+        Self {
+            load_format: LoadFormat::default(),
+            memory_thresholds: MEMORY_THRESHOLDS,
+            load_thresholds: LOAD_THRESHOLDS,
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+/// Last boot, cpu brand, avg load, memory usage
+#[derive(Render)]
+pub struct SystemInfo {
+    pub last_boot: String,
+    pub cpu: Option<String>,
+    pub avg_load: String,
+    pub memory: Option<String>,
+}
+
+#[cfg(feature = "sysinfo")]
+impl SystemInfo {
+    pub fn new(sys: &System) -> Self {
+        Self::with_options(sys, &SystemOptions::default())
+    }
+
+    /// Highlight memory usage and load (per core) past the given thresholds, and show the load
+    /// averages in the given format
+    pub fn with_options(sys: &System, options: &SystemOptions) -> Self {
+        let uptime = sys.uptime();
+        let rounded = uptime - uptime % 60;
+        let last_boot = format!(
+            "{} ago",
+            if rounded > 60 {
+                format_duration(Duration::from_secs(rounded)).to_string()
+            } else {
+                "less than one minute".to_string()
+            }
+        );
+        let cpus = sys.cpus();
+        let limits = CgroupLimits::from_root(Path::new("/"));
+        let load = sys.load_average();
+        let avg_load = format_load(
+            [load.one, load.five, load.fifteen],
+            limits.effective_cpus(cpus.len()),
+            options.load_format,
+            options.load_thresholds,
+        );
+
+        let cpu = if !cpus.is_empty() {
+            Some(cpus[0].brand().to_string())
+        } else {
+            None
+        };
+
+        let (used_mem, total_mem, limit) = match limits.effective_memory(sys.total_memory()) {
+            Some(memory) => (memory.used(), memory.limit, "limit, "),
+            None => (sys.used_memory(), sys.total_memory(), ""),
+        };
+        let percent = (used_mem as f64 / total_mem as f64) * 100.0;
+        let memory = if percent != 0.0 {
+            Some(options.memory_thresholds.paint(
+                percent / 100.0,
+                format!(
+                    "{} / {} ({limit}{percent:.01} %)",
+                    Byte::from_bytes(used_mem as u128).get_appropriate_unit(true),
+                    Byte::from_bytes(total_mem as u128).get_appropriate_unit(true),
+                ),
+            ))
+        } else {
+            None
+        };
+
+        Self {
+            last_boot,
+            cpu,
+            avg_load,
+            memory,
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+/// Fifteen minute load average per logical cpu, or per cpu of the cgroup quota, against the
+/// thresholds
+pub fn load_check(sys: &System, thresholds: Thresholds) -> Check {
+    let cpus = CgroupLimits::from_root(Path::new("/")).effective_cpus(sys.cpus().len());
+    Check::new("load", sys.load_average().fifteen / cpus, thresholds)
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for SystemInfo {
+    const ID: &'static str = "system";
+
+    fn refresh_kind() -> RefreshKind {
+        RefreshKind::new()
+            .with_cpu(CpuRefreshKind::new())
+            .with_memory()
+    }
+
+    fn from_system(sys: &System) -> Self {
+        Self::new(sys)
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+/// Union of two refresh kinds, refreshing everything either of them requires
+pub fn merge_refresh_kinds(a: RefreshKind, b: RefreshKind) -> RefreshKind {
+    let mut kind = a;
+    if let Some(processes) = b.processes() {
+        let mut merged = kind.processes().unwrap_or_default();
+        if processes.cpu() {
+            merged = merged.with_cpu();
+        }
+        if processes.disk_usage() {
+            merged = merged.with_disk_usage();
+        }
+        if processes.user() {
+            merged = merged.with_user();
+        }
+        kind = kind.with_processes(merged);
+    }
+    if let Some(cpu) = b.cpu() {
+        let mut merged = kind.cpu().unwrap_or_default();
+        if cpu.cpu_usage() {
+            merged = merged.with_cpu_usage();
+        }
+        if cpu.frequency() {
+            merged = merged.with_frequency();
+        }
+        kind = kind.with_cpu(merged);
+    }
+    if b.networks() {
+        kind = kind.with_networks();
+    }
+    if b.networks_list() {
+        kind = kind.with_networks_list();
+    }
+    if b.disks() {
+        kind = kind.with_disks();
+    }
+    if b.disks_list() {
+        kind = kind.with_disks_list();
+    }
+    if b.memory() {
+        kind = kind.with_memory();
+    }
+    if b.components() {
+        kind = kind.with_components();
+    }
+    if b.components_list() {
+        kind = kind.with_components_list();
+    }
+    if b.users_list() {
+        kind = kind.with_users_list();
+    }
+    kind
+}
+
+#[cfg(feature = "sysinfo")]
+/// Returns a [`System::new_with_specifics`] configured for the provided structs
+pub fn sys() -> System {
+    System::new_with_specifics(
+        [
+            UserInfo::refresh_kind(),
+            HostInfo::refresh_kind(),
+            CpuInfo::refresh_kind(),
+            DiskInfo::refresh_kind(),
+            MemoryInfo::refresh_kind(),
+            SystemInfo::refresh_kind(),
+        ]
+        .into_iter()
+        .fold(RefreshKind::new(), merge_refresh_kinds),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::{AnsiColors, AnsiColors::Default};
+
+    use super::{fixture::Fixture, *};
+
+    static COLORS: [DynColors; 3] = [
+        DynColors::Ansi(AnsiColors::Green),
+        DynColors::Ansi(AnsiColors::Yellow),
+        DynColors::Ansi(AnsiColors::Red),
+    ];
+
+    fn _render<R: Render>(i: R) {
+        let lines = i.render(DynColors::Ansi(Default));
+        assert!(!lines.is_empty());
+        for line in lines {
+            println!("{line}");
+        }
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn host_info() {
+        let sys = sys();
+        _render(HostInfo::new(&sys));
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn user_info() {
+        let sys = sys();
+        _render(UserInfo::new(&sys));
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn passwd_user() {
+        let root = Fixture::new(&[
+            (
+                "proc/self/status",
+                "Name:\tpunfetch\nUid:\t1000\t1001\t1001\t1001\nGid:\t100\t100\t100\t100\n",
+            ),
+            (
+                "etc/passwd",
+                "root:x:0:0::/root:/bin/sh\nalice:x:1000:100::/home/alice:/bin/sh\nbob:x:1001:100::/home/bob:/bin/sh\n",
+            ),
+        ]);
+
+        let uid = effective_uid(&root);
+        assert_eq!(uid, Some(1001));
+        assert_eq!(passwd_name(&root, 1001).as_deref(), Some("bob"));
+        assert_eq!(passwd_name(&root, 42), None);
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn refresh_kinds() {
+        let kind = merge_refresh_kinds(DiskInfo::refresh_kind(), SystemInfo::refresh_kind());
+        assert!(kind.disks_list() && kind.memory() && kind.cpu().is_some());
+        assert!(kind.processes().is_none() && !kind.users_list());
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn sys_info() {
+        let sys = sys();
+        _render(SystemInfo::new(&sys));
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn disk_info() {
+        let sys = sys();
+        _render(DiskInfo::from_system(&sys));
+    }
+
+    #[test]
+    fn generic_percent_bar() {
+        _render(PercentBar {
+            title: "Generic".to_string(),
+            total: 100.0,
+            items: vec![
+                ("foo".to_string(), 50.0, 1.0),
+                ("bar".to_string(), 25.0, 0.25),
+                ("baz".to_string(), 13.0, 0.13),
+            ],
+            colors: COLORS.to_vec(),
+            width: DEFAULT_BAR_WIDTH,
+            style: BarStyle::Solid,
+            legend: Legend::default(),
+            thresholds: Vec::new(),
+        });
+
+        _render(PercentBar {
+            title: "Full   ".to_string(),
+            total: 300.0,
+            items: vec![
+                ("large".to_string(), 100.0, 1.0),
+                ("medium".to_string(), 100.0, 1.0),
+                ("small".to_string(), 100.0, 1.0),
+            ],
+            colors: COLORS.to_vec(),
+            width: DEFAULT_BAR_WIDTH,
+            style: BarStyle::Solid,
+            legend: Legend::default(),
+            thresholds: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn load_formats() {
+        let load = [0.5, 1.25, 4.0];
+        assert_eq!(
+            format_load(load, 4.0, LoadFormat::Raw, Thresholds::new(2.0, 2.0)),
+            "0.50, 1.25, 4.00"
+        );
+        assert_eq!(
+            format_load(load, 4.0, LoadFormat::Percent, Thresholds::new(2.0, 2.0)),
+            "12 %, 31 %, 100 %"
+        );
+        // only the last average is past the thresholds
+        let highlighted = format_load(load, 4.0, LoadFormat::Percent, LOAD_THRESHOLDS);
+        assert!(highlighted.starts_with("12 %, 31 %, \u{1b}"));
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob("veth*", "veth1a2b"));
+        assert!(glob("/run/media/*", "/run/media/usb"));
+        assert!(glob("*docker*", "/var/lib/docker/overlay"));
+        assert!(glob("eth?", "eth0"));
+        assert!(glob("/mnt/disk?", "/mnt/disk1"));
+        assert!(glob("*", ""));
+        assert!(!glob("eth?", "eth10"));
+        assert!(!glob("docker0", "docker1"));
+        assert!(!glob("/mnt/disk?", "/mnt/disk10"));
+        assert!(!glob("/boot", "/boot/efi"));
+    }
+
+    #[test]
+    fn ini_values() {
+        let ini = "gtk-theme-name=\"Adwaita\"\n[Settings]\n# gtk-theme-name=Commented\ngtk-theme-name = Arc-Dark\ngtk-font-name=\n[Other]\nkey=value\n";
+        assert_eq!(
+            ini_value(ini, "", "gtk-theme-name").as_deref(),
+            Some("Adwaita")
+        );
+        assert_eq!(
+            ini_value(ini, "Settings", "gtk-theme-name").as_deref(),
+            Some("Arc-Dark")
+        );
+        assert_eq!(ini_value(ini, "Settings", "gtk-font-name"), None);
+        assert_eq!(ini_value(ini, "Settings", "key"), None);
+    }
+
+    #[test]
+    fn generic_colors() {
+        _render(ColorBar::default());
+
+        let mut colors = DEFAULT_COLORS.to_vec();
+        colors.extend(DEFAULT_COLORS);
+        _render(ColorBar(colors));
+    }
+}
@@ -0,0 +1,506 @@
+use std::{
+    fs::{read_dir, read_to_string},
+    net::{Ipv4Addr, Ipv6Addr},
+    path::Path,
+};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Interfaces hidden by default, mostly container and vm bridges
+pub const DEFAULT_IGNORED_INTERFACES: [&str; 6] =
+    ["docker*", "veth*", "br-*", "virbr*", "cni*", "flannel*"];
+
+/// `IFF_UP` from the interface flags
+const IFF_UP: u32 = 0x1;
+/// `ARPHRD_LOOPBACK`
+const LOOPBACK: &str = "772";
+
+/// How network interfaces are shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum NetworkStyle {
+    /// Hide network interfaces
+    None,
+    /// A single line with the local ipv4 addresses
+    #[default]
+    Compact,
+    /// A line per interface with its addresses, kind and link speed
+    Full,
+}
+
+/// Filtering and display options for [`NetworkInfo`]
+#[derive(Clone, Debug)]
+pub struct NetworkOptions {
+    pub style: NetworkStyle,
+    /// Only show interfaces matching one of these globs, or all when empty
+    pub include: Vec<String>,
+    /// Leave out interfaces matching any of these globs
+    pub exclude: Vec<String>,
+    /// Show mac addresses in the full style
+    pub mac: bool,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        Self {
+            style: NetworkStyle::default(),
+            include: Vec::new(),
+            exclude: DEFAULT_IGNORED_INTERFACES.map(String::from).to_vec(),
+            mac: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterfaceKind {
+    Wired,
+    Wireless,
+    Virtual,
+}
+
+/// A network interface that is up
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    pub kind: InterfaceKind,
+    pub mac: Option<String>,
+    /// Link speed in Mb/s, when the driver reports it
+    pub speed: Option<u32>,
+    /// Addresses with their prefix length, `None` for addresses outside every subnet route
+    pub ipv4: Vec<(Ipv4Addr, Option<u8>)>,
+    pub ipv6: Vec<(Ipv6Addr, u8)>,
+}
+
+/// Interfaces that are up and their local addresses, read from `/sys` and `/proc` only
+pub struct NetworkInfo {
+    pub interfaces: Vec<Interface>,
+    pub style: NetworkStyle,
+    pub mac: bool,
+}
+
+impl NetworkInfo {
+    /// Read interfaces under the given root, filtered with the given options
+    pub fn from_root(root: &Path, options: &NetworkOptions) -> Self {
+        let mut dirs: Vec<_> = read_dir(root.join("sys/class/net"))
+            .map(|dir| dir.filter_map(|e| Some(e.ok()?.path())).collect())
+            .unwrap_or_default();
+        dirs.sort();
+
+        let ipv4 = ipv4_addresses(root);
+        let ipv6 = ipv6_addresses(root);
+        let interfaces = dirs
+            .iter()
+            .filter_map(|dir| {
+                let name = dir.file_name()?.to_string_lossy().to_string();
+                if (!options.include.is_empty() && !options.include.iter().any(|p| glob(p, &name)))
+                    || options.exclude.iter().any(|p| glob(p, &name))
+                {
+                    return None;
+                }
+                let read =
+                    |file: &str| Some(read_to_string(dir.join(file)).ok()?.trim().to_string());
+
+                let flags = read("flags")
+                    .and_then(|f| u32::from_str_radix(f.trim_start_matches("0x"), 16).ok())
+                    .unwrap_or(0);
+                let up = match read("operstate").as_deref() {
+                    Some("up") => true,
+                    // e.g. tun devices and some virtual drivers never report a state
+                    Some("unknown") => flags & IFF_UP != 0,
+                    _ => false,
+                };
+                if !up || read("type").as_deref() == Some(LOOPBACK) {
+                    return None;
+                }
+
+                let kind = if dir.join("wireless").exists() || dir.join("phy80211").exists() {
+                    InterfaceKind::Wireless
+                } else if dir.join("device").exists() {
+                    InterfaceKind::Wired
+                } else {
+                    InterfaceKind::Virtual
+                };
+                Some(Interface {
+                    kind,
+                    mac: read("address").filter(|a| a.chars().any(|c| !matches!(c, '0' | ':'))),
+                    speed: read("speed")
+                        .and_then(|s| s.parse::<i64>().ok())
+                        .filter(|s| *s > 0)
+                        .map(|s| s as u32),
+                    ipv4: ipv4
+                        .iter()
+                        .filter(|(iface, ..)| *iface == name)
+                        .map(|(_, ip, prefix)| (*ip, *prefix))
+                        .collect(),
+                    ipv6: ipv6
+                        .iter()
+                        .filter(|(iface, ..)| *iface == name)
+                        .map(|(_, ip, prefix)| (*ip, *prefix))
+                        .collect(),
+                    name,
+                })
+            })
+            .collect();
+
+        Self {
+            interfaces,
+            style: options.style,
+            mac: options.mac,
+        }
+    }
+}
+
+/// Local ipv4 addresses with their interface and prefix length.
+///
+/// `/proc/net/fib_trie` lists the local addresses, and the subnet routes in `/proc/net/route`
+/// tell which interface each of them is on. Addresses outside every subnet route, like `/32` ones
+/// on WireGuard or point-to-point links, have no prefix. They go to the only routed interface
+/// without an address of its own, e.g. one with just a route to its peer, or else to the
+/// interface of the default route.
+fn ipv4_addresses(root: &Path) -> Vec<(String, Ipv4Addr, Option<u8>)> {
+    let routes: Vec<(String, u32, u32)> = read_to_string(root.join("proc/net/route"))
+        .unwrap_or_default()
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // addresses are in network order, printed as a native endian integer
+            let hex = |i: usize| {
+                let value = u32::from_str_radix(fields.get(i)?, 16).ok()?;
+                Some(u32::from(Ipv4Addr::from(value.to_ne_bytes())))
+            };
+            Some((fields.first()?.to_string(), hex(1)?, hex(7)?))
+        })
+        .collect();
+
+    let trie = read_to_string(root.join("proc/net/fib_trie")).unwrap_or_default();
+    let lines: Vec<&str> = trie.lines().collect();
+    let mut addresses = Vec::new();
+    let mut unrouted = Vec::new();
+    for pair in lines.windows(2) {
+        if pair[1].trim() != "/32 host LOCAL" {
+            continue;
+        }
+        let Some(ip) = pair[0]
+            .trim()
+            .strip_prefix("|-- ")
+            .and_then(|ip| ip.parse::<Ipv4Addr>().ok())
+        else {
+            continue;
+        };
+        if ip.is_loopback() || addresses.iter().any(|(_, a, _)| *a == ip) || unrouted.contains(&ip)
+        {
+            continue;
+        }
+        match routes
+            .iter()
+            .filter(|(_, dest, mask)| *mask != 0 && u32::from(ip) & mask == *dest)
+            .max_by_key(|(_, _, mask)| mask.count_ones())
+        {
+            Some((iface, _, mask)) => {
+                addresses.push((iface.clone(), ip, Some(mask.count_ones() as u8)))
+            }
+            None => unrouted.push(ip),
+        }
+    }
+
+    let mut bare: Vec<&String> = routes
+        .iter()
+        .map(|(iface, ..)| iface)
+        .filter(|iface| !addresses.iter().any(|(i, ..)| i == *iface))
+        .collect();
+    bare.sort();
+    bare.dedup();
+    let fallback = match bare[..] {
+        [iface] => Some(iface),
+        _ => routes
+            .iter()
+            .find(|(_, _, mask)| *mask == 0)
+            .map(|(iface, ..)| iface),
+    };
+    if let Some(iface) = fallback {
+        addresses.extend(unrouted.into_iter().map(|ip| (iface.clone(), ip, None)));
+    }
+    addresses
+}
+
+/// Global ipv6 addresses with their interface and prefix length, from `/proc/net/if_inet6`
+fn ipv6_addresses(root: &Path) -> Vec<(String, Ipv6Addr, u8)> {
+    read_to_string(root.join("proc/net/if_inet6"))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            // address, index, prefix length, scope, flags and name
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 || fields[3] != "00" {
+                return None;
+            }
+            let ip = Ipv6Addr::from(u128::from_str_radix(fields[0], 16).ok()?);
+            let prefix = u8::from_str_radix(fields[2], 16).ok()?;
+            Some((fields[5].to_string(), ip, prefix))
+        })
+        .collect()
+}
+
+/// An address with its prefix length when known, e.g. `192.168.1.23/24`
+fn cidr(ip: &Ipv4Addr, prefix: Option<u8>) -> String {
+    match prefix {
+        Some(prefix) => format!("{ip}/{prefix}"),
+        None => ip.to_string(),
+    }
+}
+
+impl Interface {
+    /// Addresses, kind, speed and optionally the mac address
+    fn describe(&self, mac: bool) -> String {
+        let mut addresses: Vec<String> = self
+            .ipv4
+            .iter()
+            .map(|(ip, prefix)| cidr(ip, *prefix))
+            .chain(
+                self.ipv6
+                    .iter()
+                    .map(|(ip, prefix)| format!("{ip}/{prefix}")),
+            )
+            .collect();
+        if addresses.is_empty() {
+            addresses.push("no address".to_string());
+        }
+
+        let mut details = vec![match self.kind {
+            InterfaceKind::Wired => "wired".to_string(),
+            InterfaceKind::Wireless => "wireless".to_string(),
+            InterfaceKind::Virtual => "virtual".to_string(),
+        }];
+        match self.speed {
+            Some(speed) if speed >= 1000 && speed % 1000 == 0 => {
+                details.push(format!("{} Gb/s", speed / 1000))
+            }
+            Some(speed) => details.push(format!("{speed} Mb/s")),
+            None => {}
+        }
+        if let (true, Some(address)) = (mac, &self.mac) {
+            details.push(address.clone());
+        }
+        format!("{} ({})", addresses.join(", "), details.join(", "))
+    }
+}
+
+impl Render for NetworkInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        match self.style {
+            NetworkStyle::None => Vec::new(),
+            NetworkStyle::Compact => {
+                let addresses: Vec<String> = self
+                    .interfaces
+                    .iter()
+                    .flat_map(|i| &i.ipv4)
+                    .map(|(ip, prefix)| cidr(ip, *prefix))
+                    .collect();
+                if addresses.is_empty() {
+                    return Vec::new();
+                }
+                vec![format!(
+                    "{}: {}",
+                    "Local IP".bold().color(color),
+                    addresses.join(", ")
+                )]
+            }
+            NetworkStyle::Full => self
+                .interfaces
+                .iter()
+                .map(|interface| {
+                    format!(
+                        "{}: {}",
+                        format!("Network ({})", interface.name).bold().color(color),
+                        interface.describe(self.mac)
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl NetworkInfo {
+    /// Interfaces of this machine, filtered with the given options
+    pub fn with_options(options: &NetworkOptions) -> Self {
+        Self::from_root(Path::new("/"), options)
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for NetworkInfo {
+    const ID: &'static str = "network";
+
+    fn from_system(_: &System) -> Self {
+        Self::with_options(&NetworkOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    const FIB_TRIE: &str = "Main:
+  +-- 0.0.0.0/0 3 0 5
+     |-- 0.0.0.0
+        /0 universe UNICAST
+     +-- 127.0.0.0/8 2 0 2
+        |-- 127.0.0.1
+           /32 host LOCAL
+     +-- 192.168.1.0/24 2 0 2
+        |-- 192.168.1.0
+           /24 link UNICAST
+        |-- 192.168.1.23
+           /32 host LOCAL
+     +-- 172.17.0.0/16 2 0 2
+        |-- 172.17.0.1
+           /32 host LOCAL
+Local:
+  +-- 0.0.0.0/0 3 0 5
+     +-- 192.168.1.0/24 2 0 2
+        |-- 192.168.1.23
+           /32 host LOCAL
+     |-- 10.8.0.2
+        /32 host LOCAL
+";
+
+    /// `/proc/net/route` holds addresses in host byte order, so build it from their octets
+    fn route() -> String {
+        let hex = |octets: [u8; 4]| format!("{:08X}", u32::from_ne_bytes(octets));
+        let mut route =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n"
+                .to_string();
+        for (iface, destination, gateway, flags, metric, mask) in [
+            ("wlan0", [0; 4], [192, 168, 1, 1], 3, 600, [0; 4]),
+            (
+                "wlan0",
+                [192, 168, 1, 0],
+                [0; 4],
+                1,
+                600,
+                [255, 255, 255, 0],
+            ),
+            ("docker0", [172, 17, 0, 0], [0; 4], 1, 0, [255, 255, 0, 0]),
+            ("tun0", [10, 8, 0, 1], [0; 4], 5, 0, [255; 4]),
+        ] {
+            route += &format!(
+                "{iface}\t{}\t{}\t{flags:04X}\t0\t0\t{metric}\t{}\t0\t0\t0\n",
+                hex(destination),
+                hex(gateway),
+                hex(mask)
+            );
+        }
+        route
+    }
+
+    fn fixture() -> Fixture {
+        let route = route();
+        Fixture::new(&[
+            ("proc/net/fib_trie", FIB_TRIE),
+            ("proc/net/route", &route),
+            (
+                "proc/net/if_inet6",
+                "fe800000000000000000000000000001 03 40 20 80    wlan0
+20010db8000000000000000000000023 03 40 00 00    wlan0
+00000000000000000000000000000001 01 80 10 80       lo
+",
+            ),
+            ("sys/class/net/lo/type", "772\n"),
+            ("sys/class/net/lo/operstate", "unknown\n"),
+            ("sys/class/net/lo/flags", "0x9\n"),
+            ("sys/class/net/wlan0/type", "1\n"),
+            ("sys/class/net/wlan0/operstate", "up\n"),
+            ("sys/class/net/wlan0/address", "aa:bb:cc:dd:ee:ff\n"),
+            ("sys/class/net/wlan0/speed", "-1\n"),
+            ("sys/class/net/wlan0/device/uevent", ""),
+            ("sys/class/net/wlan0/phy80211/name", "phy0\n"),
+            ("sys/class/net/eth0/type", "1\n"),
+            ("sys/class/net/eth0/operstate", "down\n"),
+            ("sys/class/net/eth0/device/uevent", ""),
+            ("sys/class/net/docker0/type", "1\n"),
+            ("sys/class/net/docker0/operstate", "up\n"),
+            ("sys/class/net/tun0/type", "65534\n"),
+            ("sys/class/net/tun0/operstate", "unknown\n"),
+            ("sys/class/net/tun0/flags", "0x1091\n"),
+        ])
+    }
+
+    #[test]
+    fn addresses() {
+        let root = fixture();
+        assert_eq!(
+            ipv4_addresses(&root),
+            [
+                (
+                    "wlan0".to_string(),
+                    Ipv4Addr::new(192, 168, 1, 23),
+                    Some(24)
+                ),
+                (
+                    "docker0".to_string(),
+                    Ipv4Addr::new(172, 17, 0, 1),
+                    Some(16)
+                ),
+                ("tun0".to_string(), Ipv4Addr::new(10, 8, 0, 2), None),
+            ]
+        );
+        assert_eq!(
+            ipv6_addresses(&root),
+            [("wlan0".to_string(), "2001:db8::23".parse().unwrap(), 64)]
+        );
+    }
+
+    #[test]
+    fn interfaces() {
+        let root = fixture();
+        let info = NetworkInfo::from_root(
+            &root,
+            &NetworkOptions {
+                style: NetworkStyle::Full,
+                mac: true,
+                ..Default::default()
+            },
+        );
+        let names: Vec<_> = info.interfaces.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["tun0", "wlan0"]);
+        assert_eq!(info.interfaces[0].kind, InterfaceKind::Virtual);
+
+        let lines = info.render(DynColors::Ansi(AnsiColors::Default));
+        assert!(lines[0].ends_with("10.8.0.2 (virtual)"));
+        assert!(
+            lines[1].ends_with("192.168.1.23/24, 2001:db8::23/64 (wireless, aa:bb:cc:dd:ee:ff)")
+        );
+
+        let compact = NetworkInfo {
+            style: NetworkStyle::Compact,
+            ..info
+        };
+        let lines = compact.render(DynColors::Ansi(AnsiColors::Default));
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with("192.168.1.23/24"));
+    }
+
+    #[test]
+    fn include_exclude() {
+        let root = fixture();
+        let info = NetworkInfo::from_root(
+            &root,
+            &NetworkOptions {
+                include: vec!["docker*".to_string(), "wlan*".to_string()],
+                exclude: vec!["wlan*".to_string()],
+                ..Default::default()
+            },
+        );
+        let names: Vec<_> = info.interfaces.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["docker0"]);
+    }
+}
@@ -0,0 +1,339 @@
+use std::{fs::read_to_string, path::Path};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Taint flags by bit, as listed in the kernel's `tainted-kernels` documentation
+const TAINT_FLAGS: [(char, &str); 20] = [
+    ('P', "proprietary module"),
+    ('F', "forced module load"),
+    ('S', "out of spec system"),
+    ('R', "forced module unload"),
+    ('M', "machine check"),
+    ('B', "bad page"),
+    ('U', "user request"),
+    ('D', "kernel died"),
+    ('A', "acpi table overridden"),
+    ('W', "kernel warning"),
+    ('C', "staging driver"),
+    ('I', "firmware workaround"),
+    ('O', "out of tree module"),
+    ('E', "unsigned module"),
+    ('L', "soft lockup"),
+    ('K', "live patched"),
+    ('X', "auxiliary"),
+    ('T', "struct randomization"),
+    ('N', "in kernel test"),
+    ('J', "fwctl mutating command"),
+];
+
+/// How the security posture is shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum SecurityStyle {
+    /// Hide the security posture
+    None,
+    /// A single line with the security modules, lockdown and taint flags
+    #[default]
+    Compact,
+    /// A line each for the security modules, SELinux, AppArmor, lockdown and taint
+    Full,
+}
+
+/// Active Linux security modules, their state, kernel lockdown and taint
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecurityInfo {
+    /// Security modules in the order the kernel initialized them
+    pub lsms: Vec<String>,
+    /// `enforcing` or `permissive`, `None` without SELinux
+    pub selinux: Option<String>,
+    /// Loaded profiles by mode, e.g. `("enforce", 42)`, `None` without AppArmor
+    pub apparmor: Option<Vec<(String, usize)>>,
+    /// Lockdown mode, e.g. `none` or `integrity`
+    pub lockdown: Option<String>,
+    /// Taint bitmask
+    pub tainted: Option<u64>,
+    pub style: SecurityStyle,
+}
+
+impl SecurityInfo {
+    /// Read `/sys/kernel/security`, `/sys/fs/selinux` and `/proc/sys/kernel` under the given root
+    pub fn from_root(root: &Path) -> Self {
+        let read = |path: &str| {
+            let contents = read_to_string(root.join(path)).ok()?;
+            let contents = contents.trim();
+            (!contents.is_empty()).then(|| contents.to_string())
+        };
+
+        let lsms = read("sys/kernel/security/lsm")
+            .map(|lsms| lsms.split(',').map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let selinux = read("sys/fs/selinux/enforce").map(|enforce| {
+            match enforce.as_str() {
+                "1" => "enforcing",
+                _ => "permissive",
+            }
+            .to_string()
+        });
+
+        // `name (mode)` per profile, where names may contain spaces
+        let apparmor = read("sys/module/apparmor/parameters/enabled")
+            .filter(|enabled| enabled == "Y")
+            .map(|_| {
+                let mut modes: Vec<(String, usize)> = Vec::new();
+                let profiles = read("sys/kernel/security/apparmor/profiles").unwrap_or_default();
+                for line in profiles.lines() {
+                    let Some(mode) = line
+                        .rsplit_once(" (")
+                        .and_then(|(_, mode)| mode.strip_suffix(')'))
+                    else {
+                        continue;
+                    };
+                    match modes.iter_mut().find(|(m, _)| m == mode) {
+                        Some((_, count)) => *count += 1,
+                        None => modes.push((mode.to_string(), 1)),
+                    }
+                }
+                modes
+            });
+
+        // `none [integrity] confidentiality`, with the active mode in brackets
+        let lockdown = read("sys/kernel/security/lockdown").and_then(|modes| {
+            let (_, active) = modes.split_once('[')?;
+            Some(active.split_once(']')?.0.to_string())
+        });
+
+        Self {
+            lsms,
+            selinux,
+            apparmor,
+            lockdown,
+            tainted: read("proc/sys/kernel/tainted").and_then(|t| t.parse().ok()),
+            style: SecurityStyle::default(),
+        }
+    }
+
+    /// Letter and meaning of each taint flag set
+    pub fn taint_flags(&self) -> Vec<(char, &'static str)> {
+        let tainted = self.tainted.unwrap_or(0);
+        TAINT_FLAGS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| tainted & (1 << bit) != 0)
+            .map(|(_, flag)| *flag)
+            .collect()
+    }
+
+    /// Profile counts, e.g. `42 enforce, 3 complain`
+    fn profiles(&self) -> Option<String> {
+        let modes = self.apparmor.as_ref()?;
+        let counts: Vec<String> = modes
+            .iter()
+            .map(|(mode, count)| format!("{count} {mode}"))
+            .collect();
+        (!counts.is_empty()).then(|| counts.join(", "))
+    }
+
+    /// Taint flags, e.g. `O (out of tree module), E (unsigned module)`, or `no`
+    fn taint(&self) -> Option<String> {
+        self.tainted?;
+        let flags: Vec<String> = self
+            .taint_flags()
+            .iter()
+            .map(|(flag, meaning)| format!("{flag} ({meaning})"))
+            .collect();
+        Some(if flags.is_empty() {
+            "no".to_string()
+        } else {
+            flags.join(", ")
+        })
+    }
+}
+
+impl Render for SecurityInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let line = |title: &str, value: String| format!("{}: {value}", title.bold().color(color));
+        match self.style {
+            SecurityStyle::None => Vec::new(),
+            SecurityStyle::Compact => {
+                // capability is always there, and lockdown gets its mode shown instead
+                let mut parts: Vec<String> = self
+                    .lsms
+                    .iter()
+                    .filter(|lsm| !matches!(lsm.as_str(), "capability" | "lockdown"))
+                    .map(|lsm| match lsm.as_str() {
+                        "selinux" => match &self.selinux {
+                            Some(mode) => format!("selinux ({mode})"),
+                            None => lsm.clone(),
+                        },
+                        "apparmor" => match self.profiles() {
+                            Some(profiles) => format!("apparmor ({profiles})"),
+                            None => lsm.clone(),
+                        },
+                        _ => lsm.clone(),
+                    })
+                    .collect();
+                // without securityfs the modules are unknown, but their state may still be read
+                if !self.lsms.iter().any(|lsm| lsm == "selinux") {
+                    parts.extend(
+                        self.selinux
+                            .as_ref()
+                            .map(|mode| format!("selinux ({mode})")),
+                    );
+                }
+                if !self.lsms.iter().any(|lsm| lsm == "apparmor") && self.apparmor.is_some() {
+                    let profiles = self.profiles().unwrap_or_else(|| "enabled".to_string());
+                    parts.push(format!("apparmor ({profiles})"));
+                }
+                parts.extend(
+                    self.lockdown
+                        .as_ref()
+                        .filter(|mode| *mode != "none")
+                        .map(|mode| format!("lockdown {mode}")),
+                );
+                // the letters only, like the kernel's own `Tainted: POE`
+                let flags: String = self.taint_flags().iter().map(|(flag, _)| flag).collect();
+                if !flags.is_empty() {
+                    parts.push(format!("tainted {flags}"));
+                }
+                if parts.is_empty() {
+                    return Vec::new();
+                }
+                vec![line("Security", parts.join(", "))]
+            }
+            SecurityStyle::Full => {
+                let mut lines = Vec::new();
+                if !self.lsms.is_empty() {
+                    lines.push(line("Security modules", self.lsms.join(", ")));
+                }
+                if let Some(mode) = &self.selinux {
+                    lines.push(line("SELinux", mode.clone()));
+                }
+                if self.apparmor.is_some() {
+                    let profiles = self.profiles().unwrap_or_else(|| "enabled".to_string());
+                    lines.push(line("AppArmor", profiles));
+                }
+                if let Some(mode) = &self.lockdown {
+                    lines.push(line("Lockdown", mode.clone()));
+                }
+                if let Some(taint) = self.taint() {
+                    lines.push(line("Tainted", taint));
+                }
+                lines
+            }
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for SecurityInfo {
+    const ID: &'static str = "security";
+
+    fn from_system(_: &System) -> Self {
+        Self::from_root(Path::new("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    fn render(info: &SecurityInfo) -> Vec<String> {
+        info.render(DynColors::Ansi(AnsiColors::Default))
+    }
+
+    #[test]
+    fn apparmor_lockdown_taint() {
+        let root = Fixture::new(&[
+            (
+                "sys/kernel/security/lsm",
+                "lockdown,capability,landlock,yama,apparmor,bpf",
+            ),
+            ("sys/module/apparmor/parameters/enabled", "Y\n"),
+            (
+                "sys/kernel/security/apparmor/profiles",
+                "snap.firefox.firefox (enforce)\n/usr/bin/man (enforce)\nnvidia_modprobe (complain)\nunix-chkpwd (enforce)\n",
+            ),
+            (
+                "sys/kernel/security/lockdown",
+                "none [integrity] confidentiality\n",
+            ),
+            ("proc/sys/kernel/tainted", "12289\n"),
+        ]);
+
+        let info = SecurityInfo::from_root(&root);
+        assert_eq!(
+            info.apparmor,
+            Some(vec![
+                ("enforce".to_string(), 3),
+                ("complain".to_string(), 1)
+            ])
+        );
+        assert_eq!(info.lockdown.as_deref(), Some("integrity"));
+        assert_eq!(
+            info.taint_flags(),
+            [
+                ('P', "proprietary module"),
+                ('O', "out of tree module"),
+                ('E', "unsigned module")
+            ]
+        );
+
+        let lines = render(&info);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with(
+            "landlock, yama, apparmor (3 enforce, 1 complain), bpf, lockdown integrity, tainted POE"
+        ));
+
+        let lines = render(&SecurityInfo {
+            style: SecurityStyle::Full,
+            ..info
+        });
+        assert_eq!(lines.len(), 4);
+        assert!(lines[3].ends_with("O (out of tree module), E (unsigned module)"));
+    }
+
+    #[test]
+    fn selinux_untainted() {
+        let root = Fixture::new(&[
+            ("sys/kernel/security/lsm", "capability,selinux,bpf"),
+            ("sys/fs/selinux/enforce", "0"),
+            ("proc/sys/kernel/tainted", "0\n"),
+        ]);
+
+        let info = SecurityInfo::from_root(&root);
+        assert_eq!(info.apparmor, None);
+        assert!(render(&info)[0].ends_with("selinux (permissive), bpf"));
+
+        let lines = render(&SecurityInfo {
+            style: SecurityStyle::Full,
+            ..info
+        });
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].ends_with("no"));
+    }
+
+    #[test]
+    fn without_securityfs() {
+        let root = Fixture::new(&[
+            ("sys/fs/selinux/enforce", "1\n"),
+            ("proc/sys/kernel/tainted", "524288\n"),
+        ]);
+        let info = SecurityInfo::from_root(&root);
+        assert_eq!(info.taint_flags(), [('J', "fwctl mutating command")]);
+        assert!(render(&info)[0].ends_with("selinux (enforcing), tainted J"));
+    }
+
+    #[test]
+    fn nothing_readable() {
+        let info = SecurityInfo::from_root(&Fixture::new::<&str>(&[]));
+        assert!(render(&info).is_empty());
+    }
+}
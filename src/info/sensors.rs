@@ -0,0 +1,209 @@
+use std::{
+    fs::{read_dir, read_to_string},
+    path::Path,
+};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Used for sensors that don't report their own limits, in °C
+pub const TEMP_THRESHOLDS: Thresholds = Thresholds::new(80.0, 95.0);
+
+/// Readings preferred as the main temperature of a chip, e.g. the package rather than one core
+const MAIN_LABELS: [&str; 5] = ["Package id 0", "Tctl", "Tdie", "Composite", "edge"];
+
+/// Unit temperatures are shown in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum TempUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TempUnit {
+    pub fn format(self, celsius: f64) -> String {
+        match self {
+            Self::Celsius => format!("{celsius:.1} °C"),
+            Self::Fahrenheit => format!("{:.1} °F", celsius * 9.0 / 5.0 + 32.0),
+        }
+    }
+}
+
+/// Main temperature of a hwmon chip
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sensor {
+    /// What the chip measures, e.g. `Cpu` or `Nvme`
+    pub kind: &'static str,
+    pub celsius: f64,
+    /// Warn and critical temperatures in °C, from the chip's max and crit when reported
+    pub thresholds: Thresholds,
+}
+
+/// Cpu, gpu and nvme temperatures from `/sys/class/hwmon`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SensorsInfo {
+    pub sensors: Vec<Sensor>,
+    pub unit: TempUnit,
+}
+
+/// Kind of device a hwmon driver belongs to, `None` for ones not worth showing
+fn kind(driver: &str) -> Option<&'static str> {
+    match driver {
+        "coretemp" | "k10temp" | "zenpower" | "cpu_thermal" | "soc_thermal" => Some("Cpu"),
+        "amdgpu" | "radeon" | "nouveau" | "i915" | "xe" => Some("Gpu"),
+        "nvme" => Some("Nvme"),
+        _ => None,
+    }
+}
+
+impl SensorsInfo {
+    /// Read every known chip under `/sys/class/hwmon` of the given root
+    pub fn from_root(root: &Path) -> Self {
+        let mut chips: Vec<_> = read_dir(root.join("sys/class/hwmon"))
+            .map(|dir| dir.filter_map(|e| Some(e.ok()?.path())).collect())
+            .unwrap_or_default();
+        // hwmon10 after hwmon9
+        chips.sort_by_key(|p| {
+            let name = p.file_name().unwrap_or_default().to_string_lossy();
+            (name.len(), name.to_string())
+        });
+
+        let sensors = chips
+            .iter()
+            .filter_map(|chip| {
+                let kind = kind(read_to_string(chip.join("name")).ok()?.trim())?;
+                sensor(chip, kind)
+            })
+            .collect();
+        Self {
+            sensors,
+            unit: TempUnit::default(),
+        }
+    }
+}
+
+/// Main temperature of one chip, with its limits
+fn sensor(chip: &Path, kind: &'static str) -> Option<Sensor> {
+    let read = |file: String| read_to_string(chip.join(file)).ok();
+    let millis = |file: String| read(file)?.trim().parse::<f64>().ok().map(|m| m / 1000.0);
+
+    let mut inputs: Vec<u32> = read_dir(chip)
+        .ok()?
+        .filter_map(|e| {
+            let name = e.ok()?.file_name().to_string_lossy().to_string();
+            name.strip_prefix("temp")?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    inputs.sort_unstable();
+
+    let main = inputs
+        .iter()
+        .find(|i| {
+            read(format!("temp{i}_label")).is_some_and(|label| MAIN_LABELS.contains(&label.trim()))
+        })
+        .or(inputs.first())?;
+
+    let celsius = millis(format!("temp{main}_input"))?;
+    let thresholds = match (
+        millis(format!("temp{main}_max")),
+        millis(format!("temp{main}_crit")),
+    ) {
+        (Some(max), Some(crit)) if max > 0.0 && max <= crit => Thresholds::new(max, crit),
+        (_, Some(crit)) if crit > 0.0 => Thresholds::new(crit - 10.0, crit),
+        _ => TEMP_THRESHOLDS,
+    };
+    Some(Sensor {
+        kind,
+        celsius,
+        thresholds,
+    })
+}
+
+impl Render for SensorsInfo {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        let mut lines = Vec::new();
+        for kind in ["Cpu", "Gpu", "Nvme"] {
+            let temps: Vec<String> = self
+                .sensors
+                .iter()
+                .filter(|s| s.kind == kind)
+                .map(|s| s.thresholds.paint(s.celsius, self.unit.format(s.celsius)))
+                .collect();
+            if !temps.is_empty() {
+                lines.push(format!(
+                    "{}: {}",
+                    format!("{kind} temp").bold().color(color),
+                    temps.join(", ")
+                ));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for SensorsInfo {
+    const ID: &'static str = "sensors";
+
+    fn from_system(_: &System) -> Self {
+        Self::from_root(Path::new("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use owo_colors::AnsiColors;
+
+    use super::{super::fixture::Fixture, *};
+
+    #[test]
+    fn hwmon_tree() {
+        let root = Fixture::new(&[
+            ("sys/class/hwmon/hwmon0/name", "acpitz\n"),
+            ("sys/class/hwmon/hwmon0/temp1_input", "27800\n"),
+            ("sys/class/hwmon/hwmon1/name", "coretemp\n"),
+            ("sys/class/hwmon/hwmon1/temp2_input", "48000\n"),
+            ("sys/class/hwmon/hwmon1/temp2_label", "Core 0\n"),
+            ("sys/class/hwmon/hwmon1/temp1_input", "52000\n"),
+            ("sys/class/hwmon/hwmon1/temp1_label", "Package id 0\n"),
+            ("sys/class/hwmon/hwmon1/temp1_max", "80000\n"),
+            ("sys/class/hwmon/hwmon1/temp1_crit", "100000\n"),
+            ("sys/class/hwmon/hwmon10/name", "nvme\n"),
+            ("sys/class/hwmon/hwmon10/temp1_input", "84850\n"),
+            ("sys/class/hwmon/hwmon10/temp1_label", "Composite\n"),
+            ("sys/class/hwmon/hwmon10/temp1_crit", "89850\n"),
+            ("sys/class/hwmon/hwmon2/name", "nvme\n"),
+            ("sys/class/hwmon/hwmon2/temp1_input", "38850\n"),
+        ]);
+
+        let info = SensorsInfo::from_root(&root);
+        let temps: Vec<_> = info.sensors.iter().map(|s| (s.kind, s.celsius)).collect();
+        assert_eq!(temps, [("Cpu", 52.0), ("Nvme", 38.85), ("Nvme", 84.85)]);
+        assert_eq!(info.sensors[0].thresholds, Thresholds::new(80.0, 100.0));
+        assert_eq!(info.sensors[1].thresholds, TEMP_THRESHOLDS);
+        assert_eq!(info.sensors[2].thresholds.status(84.85), Status::Warn);
+
+        let info = SensorsInfo {
+            unit: TempUnit::Fahrenheit,
+            ..info
+        };
+        let lines = info.render(DynColors::Ansi(AnsiColors::Default));
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("125.6 °F"));
+        assert!(lines[1].contains("101.9 °F, "));
+    }
+
+    #[test]
+    fn no_hwmon() {
+        let info = SensorsInfo::from_root(&Fixture::new::<&str>(&[]));
+        assert!(info.render(DynColors::Ansi(AnsiColors::Default)).is_empty());
+    }
+}
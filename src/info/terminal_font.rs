@@ -0,0 +1,577 @@
+use std::{
+    fmt::{Display, Formatter},
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use lazy_static::lazy_static;
+use owo_colors::{DynColors, OwoColorize};
+use regex::Regex;
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Includes followed before giving up, in case config files include each other
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+lazy_static! {
+    /// `wezterm.font("...")`, `wezterm.font { family = "..." }` or `wezterm.font_with_fallback`
+    static ref WEZTERM_FAMILY: Regex = Regex::new(
+        r#"wezterm\.font(?:_with_fallback)?[\s(]*\{?\s*(?:family\s*=\s*)?["']([^"']+)["']"#,
+    )
+    .unwrap();
+    static ref WEZTERM_SIZE: Regex = Regex::new(r"font_size\s*=\s*([0-9.]+)").unwrap();
+}
+
+/// Terminal emulators whose font can be read from their config files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Terminal {
+    Kitty,
+    Alacritty,
+    WezTerm,
+    Foot,
+    Ghostty,
+}
+
+impl Terminal {
+    /// Detect the emulator from the variables it sets for the programs running in it
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let program = var("TERM_PROGRAM").unwrap_or_default().to_lowercase();
+        let term = var("TERM").unwrap_or_default();
+        if program == "wezterm" || term == "wezterm" {
+            Some(Self::WezTerm)
+        } else if program == "ghostty" || term == "xterm-ghostty" {
+            Some(Self::Ghostty)
+        } else if program == "kitty" || term == "xterm-kitty" || var("KITTY_WINDOW_ID").is_some() {
+            Some(Self::Kitty)
+        } else if term == "alacritty" || var("ALACRITTY_WINDOW_ID").is_some() {
+            Some(Self::Alacritty)
+        } else if term.starts_with("foot") {
+            Some(Self::Foot)
+        } else {
+            None
+        }
+    }
+
+    /// Font from the emulator's config, `None` when it isn't set there
+    pub fn font(self, home: &Path, config: &Path) -> Option<Font> {
+        let mut font = Partial::default();
+        match self {
+            Self::Kitty => kitty(&config.join("kitty/kitty.conf"), home, &mut font, 0),
+            Self::Alacritty => {
+                let path = [
+                    config.join("alacritty/alacritty.toml"),
+                    config.join("alacritty/alacritty.yml"),
+                    config.join("alacritty.toml"),
+                    config.join("alacritty.yml"),
+                    home.join(".alacritty.toml"),
+                    home.join(".alacritty.yml"),
+                ]
+                .into_iter()
+                .find(|path| path.exists())?;
+                alacritty(&path, home, &mut font, 0)
+            }
+            Self::WezTerm => {
+                let path = [
+                    config.join("wezterm/wezterm.lua"),
+                    home.join(".wezterm.lua"),
+                ]
+                .into_iter()
+                .find(|path| path.exists())?;
+                wezterm(&read_to_string(path).ok()?, &mut font)
+            }
+            Self::Foot => foot(&config.join("foot/foot.ini"), home, &mut font, 0),
+            Self::Ghostty => ghostty(&config.join("ghostty/config"), home, &mut font, 0),
+        }
+        font.finish()
+    }
+}
+
+/// Font family and size in points
+#[derive(Clone, Debug, PartialEq)]
+pub struct Font {
+    pub family: String,
+    pub size: Option<f64>,
+}
+
+impl Display for Font {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.size {
+            Some(size) => write!(f, "{} {size}", self.family),
+            None => write!(f, "{}", self.family),
+        }
+    }
+}
+
+/// Font settings found so far, later files override earlier ones
+#[derive(Default)]
+struct Partial {
+    family: Option<String>,
+    size: Option<f64>,
+}
+
+impl Partial {
+    fn merge(&mut self, other: Self) {
+        self.family = other.family.or(self.family.take());
+        self.size = other.size.or(self.size);
+    }
+
+    fn finish(self) -> Option<Font> {
+        Some(Font {
+            family: self.family.filter(|f| !f.is_empty())?,
+            size: self.size,
+        })
+    }
+}
+
+/// Path of an included file, relative to the including one unless it is absolute or in `~`
+fn resolve(include: &str, from: &Path, home: &Path) -> PathBuf {
+    let include = include.trim().trim_matches(|c| c == '"' || c == '\'');
+    match include.strip_prefix("~/") {
+        Some(path) => home.join(path),
+        None => from.parent().unwrap_or(from).join(include),
+    }
+}
+
+/// Quoted strings in some text, with either quote
+fn quoted(text: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(['"', '\'']) {
+        let quote = rest[start..].chars().next().unwrap_or('"');
+        let Some(len) = rest[start + 1..].find(quote) else {
+            break;
+        };
+        strings.push(rest[start + 1..start + 1 + len].to_string());
+        rest = &rest[start + len + 2..];
+    }
+    strings
+}
+
+/// `kitty.conf`, with `key value` lines and `include`s read in place
+fn kitty(path: &Path, home: &Path, font: &mut Partial, depth: usize) {
+    let Ok(contents) = read_to_string(path) else {
+        return;
+    };
+    for line in contents.lines().map(str::trim) {
+        let (key, value) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(k, v)| (k, v.trim()));
+        match key {
+            // newer versions take `family="..." style=...`
+            "font_family" if value.starts_with("family=") => {
+                font.family = quoted(value)
+                    .into_iter()
+                    .next()
+                    .or_else(|| value.strip_prefix("family=").map(str::to_string))
+            }
+            "font_family" => font.family = Some(value.to_string()),
+            "font_size" => font.size = value.parse().ok().or(font.size),
+            "include" if depth < MAX_INCLUDE_DEPTH => {
+                kitty(&resolve(value, path, home), home, font, depth + 1)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `alacritty.toml` or the older `alacritty.yml`, imports are read first so the file overrides
+/// them
+fn alacritty(path: &Path, home: &Path, font: &mut Partial, depth: usize) {
+    let Ok(contents) = read_to_string(path) else {
+        return;
+    };
+    let (imports, own) = if path.extension().is_some_and(|ext| ext == "toml") {
+        alacritty_toml(&contents)
+    } else {
+        alacritty_yaml(&contents)
+    };
+    if depth < MAX_INCLUDE_DEPTH {
+        for import in imports {
+            alacritty(&resolve(&import, path, home), home, font, depth + 1);
+        }
+    }
+    font.merge(own);
+}
+
+fn alacritty_toml(contents: &str) -> (Vec<String>, Partial) {
+    let (mut imports, mut font) = (Vec::new(), Partial::default());
+    let (mut section, mut in_import) = ("", false);
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        // an import array spanning several lines
+        if in_import {
+            imports.extend(quoted(line));
+            in_import = !line.contains(']');
+            continue;
+        }
+        if line.starts_with('[') {
+            section = line.trim_matches(|c| c == '[' || c == ']').trim();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match (section, key.trim()) {
+            ("" | "general", "import") => {
+                imports.extend(quoted(value));
+                in_import = !value.contains(']');
+            }
+            ("font", "size") => font.size = value.parse().ok(),
+            ("font.normal", "family") => font.family = quoted(value).into_iter().next(),
+            // `normal = { family = "...", style = "..." }`
+            ("font", "normal") => {
+                font.family = value
+                    .split_once("family")
+                    .and_then(|(_, rest)| quoted(rest).into_iter().next())
+            }
+            _ => {}
+        }
+    }
+    (imports, font)
+}
+
+fn alacritty_yaml(contents: &str) -> (Vec<String>, Partial) {
+    let (mut imports, mut font) = (Vec::new(), Partial::default());
+    // keys of the parent mappings, with their indentation
+    let mut parents: Vec<(usize, &str)> = Vec::new();
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if parents.last().is_some_and(|(_, key)| *key == "import") {
+                imports.push(item.to_string());
+            }
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        while parents.last().is_some_and(|(i, _)| *i >= indent) {
+            parents.pop();
+        }
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        let path: Vec<&str> = parents.iter().map(|(_, k)| *k).chain([key]).collect();
+        match (path.as_slice(), value) {
+            (_, "") => parents.push((indent, key)),
+            (["font", "size"], size) => font.size = size.parse().ok(),
+            (["font", "normal", "family"], family) => font.family = Some(family.to_string()),
+            _ => {}
+        }
+    }
+    (imports, font)
+}
+
+/// `wezterm.lua`, a lua program, only the usual ways to set a font are understood
+fn wezterm(contents: &str, font: &mut Partial) {
+    let code: String = contents
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("--"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    font.family = WEZTERM_FAMILY.captures(&code).map(|c| c[1].to_string());
+    font.size = WEZTERM_SIZE.captures(&code).and_then(|c| c[1].parse().ok());
+}
+
+/// `foot.ini`, where the font is a fontconfig pattern like `Fira Code:size=11`
+fn foot(path: &Path, home: &Path, font: &mut Partial, depth: usize) {
+    let Ok(contents) = read_to_string(path) else {
+        return;
+    };
+    let mut section = "main";
+    for line in contents.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match (section, key.trim()) {
+            ("main", "font") => {
+                // fallback fonts follow the first one, separated by commas
+                let mut pattern = value.split(',').next().unwrap_or_default().split(':');
+                font.family = pattern.next().map(|f| f.trim().to_string());
+                font.size = pattern
+                    .find_map(|attr| attr.trim().strip_prefix("size="))
+                    .and_then(|size| size.parse().ok());
+            }
+            ("main", "include") if depth < MAX_INCLUDE_DEPTH => {
+                foot(&resolve(value, path, home), home, font, depth + 1)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Ghostty `config`, where the first `font-family` is the primary font and `config-file`s are
+/// read after the file. Families in config files add fallbacks instead of replacing the primary
+/// font, unless an empty one resets the list first, so the first family read wins.
+fn ghostty(path: &Path, home: &Path, font: &mut Partial, depth: usize) {
+    let Ok(contents) = read_to_string(path) else {
+        return;
+    };
+    let mut includes = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"');
+        match key.trim() {
+            // repeated families are fallbacks, and an empty one resets the list
+            "font-family" if value.is_empty() => font.family = None,
+            "font-family" => {
+                font.family.get_or_insert_with(|| value.to_string());
+            }
+            "font-size" => font.size = value.parse().ok().or(font.size),
+            // a leading `?` marks optional files
+            "config-file" => includes.push(value.trim_start_matches('?').to_string()),
+            _ => {}
+        }
+    }
+    if depth < MAX_INCLUDE_DEPTH {
+        for include in includes {
+            ghostty(&resolve(&include, path, home), home, font, depth + 1);
+        }
+    }
+}
+
+/// Font of the terminal emulator punfetch runs in
+#[derive(Clone, Debug, Default, PartialEq, Eq, Render)]
+pub struct TerminalFontInfo {
+    pub terminal_font: Option<String>,
+}
+
+impl TerminalFontInfo {
+    /// Read the font of the given emulator from the given home and config directory
+    pub fn from_dirs(terminal: Option<Terminal>, home: &Path, config: &Path) -> Self {
+        Self {
+            terminal_font: terminal
+                .and_then(|terminal| terminal.font(home, config))
+                .map(|font| font.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for TerminalFontInfo {
+    const ID: &'static str = "terminal_font";
+
+    fn from_system(_: &System) -> Self {
+        let terminal = Terminal::detect(|var| env::var(var).ok());
+        home_dirs()
+            .map(|(home, config)| Self::from_dirs(terminal, &home, &config))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::fixture::Fixture, *};
+
+    fn font(terminal: Terminal, files: &[(&str, &str)]) -> Option<String> {
+        let home = Fixture::new(files);
+        TerminalFontInfo::from_dirs(Some(terminal), &home, &home.join(".config")).terminal_font
+    }
+
+    #[test]
+    fn detect() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |var: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == var)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            Terminal::detect(env(&[("TERM", "xterm-kitty")])),
+            Some(Terminal::Kitty)
+        );
+        assert_eq!(
+            Terminal::detect(env(&[
+                ("TERM", "xterm-256color"),
+                ("TERM_PROGRAM", "WezTerm")
+            ])),
+            Some(Terminal::WezTerm)
+        );
+        assert_eq!(
+            Terminal::detect(env(&[("TERM", "foot-extra")])),
+            Some(Terminal::Foot)
+        );
+        assert_eq!(Terminal::detect(env(&[("TERM", "xterm-256color")])), None);
+    }
+
+    #[test]
+    fn kitty_includes() {
+        assert_eq!(
+            font(
+                Terminal::Kitty,
+                &[
+                    (
+                        ".config/kitty/kitty.conf",
+                        "font_family Fira Code\ninclude fonts.conf\nfont_size 12.0\n",
+                    ),
+                    (
+                        ".config/kitty/fonts.conf",
+                        "# overrides\nfont_family      JetBrainsMono Nerd Font\nfont_size 11\n",
+                    ),
+                ]
+            )
+            .as_deref(),
+            Some("JetBrainsMono Nerd Font 12")
+        );
+        assert_eq!(
+            font(
+                Terminal::Kitty,
+                &[(
+                    ".config/kitty/kitty.conf",
+                    "font_family family=\"Iosevka Term\" style=Medium\n",
+                )]
+            )
+            .as_deref(),
+            Some("Iosevka Term")
+        );
+    }
+
+    #[test]
+    fn alacritty_toml_and_yaml() {
+        assert_eq!(
+            font(
+                Terminal::Alacritty,
+                &[
+                    (
+                        ".config/alacritty/alacritty.toml",
+                        "[general]\nimport = [\n  \"~/.config/alacritty/font.toml\",\n]\n\n[font]\nsize = 10.5\n",
+                    ),
+                    (
+                        ".config/alacritty/font.toml",
+                        "[font]\nnormal = { family = \"Hack\", style = \"Regular\" }\nsize = 9\n",
+                    ),
+                ]
+            )
+            .as_deref(),
+            Some("Hack 10.5")
+        );
+        assert_eq!(
+            font(
+                Terminal::Alacritty,
+                &[(
+                    ".alacritty.yml",
+                    "window:\n  opacity: 0.9\nfont:\n  normal:\n    family: \"Cascadia Code\"\n    style: Regular\n  size: 13\n",
+                )]
+            )
+            .as_deref(),
+            Some("Cascadia Code 13")
+        );
+    }
+
+    #[test]
+    fn wezterm_lua() {
+        assert_eq!(
+            font(
+                Terminal::WezTerm,
+                &[(
+                    ".config/wezterm/wezterm.lua",
+                    "local wezterm = require 'wezterm'\n-- config.font = wezterm.font 'Old'\nconfig.font = wezterm.font_with_fallback { 'Monaspace Neon', 'Noto Color Emoji' }\nconfig.font_size = 14.0\nreturn config\n",
+                )]
+            )
+            .as_deref(),
+            Some("Monaspace Neon 14")
+        );
+    }
+
+    #[test]
+    fn foot_and_ghostty() {
+        assert_eq!(
+            font(
+                Terminal::Foot,
+                &[
+                    (
+                        ".config/foot/foot.ini",
+                        "include=~/.config/foot/font.ini\n[colors]\nalpha=0.9\n",
+                    ),
+                    (
+                        ".config/foot/font.ini",
+                        "font=Fira Code:size=11:weight=medium, Noto Color Emoji:size=10\n",
+                    ),
+                ]
+            )
+            .as_deref(),
+            Some("Fira Code 11")
+        );
+        assert_eq!(
+            font(
+                Terminal::Ghostty,
+                &[
+                    (
+                        ".config/ghostty/config",
+                        "font-family = \"Berkeley Mono\"\nfont-family = Symbols Nerd Font\nconfig-file = ?local\nconfig-file = ?missing\n",
+                    ),
+                    (".config/ghostty/local", "font-size = 13\n"),
+                ]
+            )
+            .as_deref(),
+            Some("Berkeley Mono 13")
+        );
+    }
+
+    #[test]
+    fn ghostty_includes() {
+        let ghostty = |main: &'static str, include: &'static str| {
+            font(
+                Terminal::Ghostty,
+                &[
+                    (".config/ghostty/config", main),
+                    (".config/ghostty/fonts", include),
+                ],
+            )
+        };
+        assert_eq!(
+            ghostty("config-file = fonts\n", "font-family = Iosevka\n").as_deref(),
+            Some("Iosevka")
+        );
+        // the include is read after the file, only adding a fallback
+        assert_eq!(
+            ghostty(
+                "config-file = fonts\nfont-family = Hack\n",
+                "font-family = Iosevka\n"
+            )
+            .as_deref(),
+            Some("Hack")
+        );
+        assert_eq!(
+            ghostty(
+                "font-family = Hack\nconfig-file = fonts\n",
+                "font-family = \"\"\nfont-family = Iosevka\n"
+            )
+            .as_deref(),
+            Some("Iosevka")
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(font(Terminal::Kitty, &[]), None);
+        assert_eq!(
+            font(
+                Terminal::Foot,
+                &[(".config/foot/foot.ini", "[main]\ninclude=foot.ini\n")]
+            ),
+            None
+        );
+        let home = Fixture::new::<&str>(&[]);
+        assert_eq!(
+            TerminalFontInfo::from_dirs(None, &home, &home.join(".config")),
+            TerminalFontInfo::default()
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// Theme, icons, cursor and font of one toolkit
+#[derive(Default)]
+struct Toolkit {
+    name: &'static str,
+    theme: Option<String>,
+    icons: Option<String>,
+    cursor: Option<String>,
+    font: Option<String>,
+}
+
+/// Gtk, KDE and Qt theme, icons, cursor and font from the user's config files, each followed by
+/// the toolkits using it
+#[derive(Clone, Debug, Default, PartialEq, Eq, Render)]
+pub struct ThemeInfo {
+    pub theme: Option<String>,
+    pub icons: Option<String>,
+    pub cursor: Option<String>,
+    pub font: Option<String>,
+}
+
+impl ThemeInfo {
+    /// Read the config files of the given home and config directory
+    pub fn from_dirs(home: &Path, config: &Path) -> Self {
+        let read = |path: PathBuf| read_to_string(path).unwrap_or_default();
+
+        let mut toolkits = vec![gtk("GTK2", &read(home.join(".gtkrc-2.0")), "")];
+        for (name, dir) in [("GTK3", "gtk-3.0"), ("GTK4", "gtk-4.0")] {
+            let settings = read(config.join(dir).join("settings.ini"));
+            toolkits.push(gtk(name, &settings, "Settings"));
+        }
+
+        let kde = read(config.join("kdeglobals"));
+        toolkits.push(Toolkit {
+            name: "KDE",
+            theme: ini_value(&kde, "General", "ColorScheme")
+                .or_else(|| ini_value(&kde, "KDE", "widgetStyle")),
+            icons: ini_value(&kde, "Icons", "Theme"),
+            cursor: ini_value(&read(config.join("kcminputrc")), "Mouse", "cursorTheme"),
+            font: ini_value(&kde, "General", "font").and_then(|f| qt_font(&f)),
+        });
+
+        for (name, file) in [("Qt5", "qt5ct/qt5ct.conf"), ("Qt6", "qt6ct/qt6ct.conf")] {
+            let conf = read(config.join(file));
+            toolkits.push(Toolkit {
+                name,
+                theme: ini_value(&conf, "Appearance", "style"),
+                icons: ini_value(&conf, "Appearance", "icon_theme"),
+                font: ini_value(&conf, "Fonts", "general").and_then(|f| qt_font(&f)),
+                ..Default::default()
+            });
+        }
+
+        // the default cursor of X11 apps, set by most cursor theme installers
+        let cursor = [".icons", ".local/share/icons"].iter().find_map(|dir| {
+            let index = read(home.join(dir).join("default/index.theme"));
+            ini_value(&index, "Icon Theme", "Inherits")
+        });
+        toolkits.push(Toolkit {
+            name: "X11",
+            cursor,
+            ..Default::default()
+        });
+
+        Self {
+            theme: combine(toolkits.iter().map(|t| (t.name, &t.theme))),
+            icons: combine(toolkits.iter().map(|t| (t.name, &t.icons))),
+            cursor: combine(toolkits.iter().map(|t| (t.name, &t.cursor))),
+            font: combine(toolkits.iter().map(|t| (t.name, &t.font))),
+        }
+    }
+}
+
+/// Settings from a gtkrc or gtk `settings.ini`
+fn gtk(name: &'static str, contents: &str, section: &str) -> Toolkit {
+    Toolkit {
+        name,
+        theme: ini_value(contents, section, "gtk-theme-name"),
+        icons: ini_value(contents, section, "gtk-icon-theme-name"),
+        cursor: ini_value(contents, section, "gtk-cursor-theme-name"),
+        font: ini_value(contents, section, "gtk-font-name"),
+    }
+}
+
+/// Family and size of a serialized `QFont`, e.g. `Noto Sans,10,-1,5,50,0,0,0,0,0`
+fn qt_font(font: &str) -> Option<String> {
+    // qt5ct stores some fonts as binary variants
+    if font.starts_with('@') {
+        return None;
+    }
+    let mut fields = font.split(',');
+    let family = fields.next()?.trim();
+    match fields.next().map(str::trim) {
+        Some(size) if !size.is_empty() => Some(format!("{family} {size}")),
+        _ => Some(family.to_string()),
+    }
+}
+
+/// Distinct values, each followed by the toolkits using it, e.g. `Breeze [KDE/GTK3]`
+fn combine<'a>(values: impl Iterator<Item = (&'static str, &'a Option<String>)>) -> Option<String> {
+    let mut grouped: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (name, value) in values {
+        let Some(value) = value else { continue };
+        match grouped.iter_mut().find(|(v, _)| v == value) {
+            Some((_, names)) => names.push(name),
+            None => grouped.push((value, vec![name])),
+        }
+    }
+    let values: Vec<String> = grouped
+        .iter()
+        .map(|(value, names)| format!("{value} [{}]", names.join("/")))
+        .collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for ThemeInfo {
+    const ID: &'static str = "theme";
+
+    fn from_system(_: &System) -> Self {
+        home_dirs()
+            .map(|(home, config)| Self::from_dirs(&home, &config))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::fixture::Fixture, *};
+
+    #[test]
+    fn gtk_and_kde() {
+        let home = Fixture::new(&[
+            (
+                ".gtkrc-2.0",
+                "gtk-theme-name=\"Breeze\"\ngtk-icon-theme-name=\"Papirus-Dark\"\n",
+            ),
+            (
+                ".config/gtk-3.0/settings.ini",
+                "[Settings]\ngtk-theme-name=Breeze\ngtk-icon-theme-name=Papirus-Dark\ngtk-cursor-theme-name=Bibata-Modern-Ice\ngtk-font-name=Noto Sans 10\n",
+            ),
+            (
+                ".config/kdeglobals",
+                "[General]\nColorScheme=BreezeDark\nfont=Noto Sans,10,-1,5,50,0,0,0,0,0\n\n[Icons]\nTheme=Papirus-Dark\n",
+            ),
+            (".config/kcminputrc", "[Mouse]\ncursorTheme=Bibata-Modern-Ice\n"),
+        ]);
+
+        let info = ThemeInfo::from_dirs(&home, &home.join(".config"));
+        assert_eq!(
+            info,
+            ThemeInfo {
+                theme: Some("Breeze [GTK2/GTK3], BreezeDark [KDE]".to_string()),
+                icons: Some("Papirus-Dark [GTK2/GTK3/KDE]".to_string()),
+                cursor: Some("Bibata-Modern-Ice [GTK3/KDE]".to_string()),
+                font: Some("Noto Sans 10 [GTK3/KDE]".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn qt5ct_and_cursor_index() {
+        let home = Fixture::new(&[
+            (
+                "config/qt5ct/qt5ct.conf",
+                "[Appearance]\nicon_theme=breeze\nstyle=Fusion\n\n[Fonts]\nfixed=@Variant(\\0\\0\\0@\\0)\ngeneral=\"Inter,11,-1,5,50,0,0,0,0,0\"\n",
+            ),
+            (
+                ".icons/default/index.theme",
+                "[Icon Theme]\nName=Default\nInherits=phinger-cursors\n",
+            ),
+        ]);
+
+        let info = ThemeInfo::from_dirs(&home, &home.join("config"));
+        assert_eq!(info.theme.as_deref(), Some("Fusion [Qt5]"));
+        assert_eq!(info.icons.as_deref(), Some("breeze [Qt5]"));
+        assert_eq!(info.cursor.as_deref(), Some("phinger-cursors [X11]"));
+        assert_eq!(info.font.as_deref(), Some("Inter 11 [Qt5]"));
+    }
+
+    #[test]
+    fn empty_home() {
+        let home = Fixture::new::<&str>(&[]);
+        assert_eq!(
+            ThemeInfo::from_dirs(&home, &home.join(".config")),
+            ThemeInfo::default()
+        );
+    }
+}
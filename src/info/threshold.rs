@@ -0,0 +1,191 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use owo_colors::{colored::Color, DynColors, OwoColorize};
+
+/// Disks are flagged above 90 % full
+pub const DISK_THRESHOLDS: Thresholds = Thresholds::new(0.9, 0.95);
+/// Memory is flagged above 80 % used
+pub const MEMORY_THRESHOLDS: Thresholds = Thresholds::new(0.8, 0.9);
+/// Swap is flagged above 50 % used
+pub const SWAP_THRESHOLDS: Thresholds = Thresholds::new(0.5, 0.8);
+/// Load is flagged above 70 % of the core count
+pub const LOAD_THRESHOLDS: Thresholds = Thresholds::new(0.7, 1.0);
+
+/// Severity of a value compared to its [`Thresholds`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    #[default]
+    Normal,
+    Warn,
+    Critical,
+}
+
+impl Status {
+    /// Process exit code, following the monitoring plugin convention
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Color overriding the usual palette, if any
+    pub fn color(self) -> Option<DynColors> {
+        match self {
+            Self::Normal => None,
+            Self::Warn => Some(DynColors::Ansi(Color::Yellow)),
+            Self::Critical => Some(DynColors::Ansi(Color::Red)),
+        }
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Normal => "OK",
+            Self::Warn => "WARNING",
+            Self::Critical => "CRITICAL",
+        })
+    }
+}
+
+/// Warn and critical levels for a value, usually fractions (e.g. `0.9` for 90 %)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Thresholds {
+    pub warn: f64,
+    pub critical: f64,
+}
+
+impl Thresholds {
+    pub const fn new(warn: f64, critical: f64) -> Self {
+        Self { warn, critical }
+    }
+
+    /// Status of a value, values that aren't a number are always normal
+    pub fn status(&self, value: f64) -> Status {
+        if value >= self.critical {
+            Status::Critical
+        } else if value >= self.warn {
+            Status::Warn
+        } else {
+            Status::Normal
+        }
+    }
+
+    /// Color the text to match the status of the value, leaving it as is when normal
+    pub fn paint<T: Display>(&self, value: f64, text: T) -> String {
+        match self.status(value).color() {
+            Some(color) => text.color(color).to_string(),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// A value checked against its thresholds, e.g. the usage of one disk
+#[derive(Clone, Debug, PartialEq)]
+pub struct Check {
+    pub name: String,
+    /// Checked value, as a fraction
+    pub value: f64,
+    pub thresholds: Thresholds,
+}
+
+impl Check {
+    pub fn new<S: ToString>(name: S, value: f64, thresholds: Thresholds) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            thresholds,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        self.thresholds.status(self.value)
+    }
+}
+
+/// One line summary, e.g. `CRITICAL disk /home: 96.2 % (warn 90 %, critical 95 %)`
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {:.1} % (warn {} %, critical {} %)",
+            self.status(),
+            self.name,
+            self.value * 100.0,
+            percent(self.thresholds.warn),
+            percent(self.thresholds.critical),
+        )
+    }
+}
+
+/// A ratio as a percent rounded to two decimals, so `0.29` shows as `29` instead of float noise
+fn percent(ratio: f64) -> f64 {
+    (ratio * 10000.0).round() / 100.0
+}
+
+/// Parse `WARN,CRITICAL` percentages, e.g. `80,90`
+impl FromStr for Thresholds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (warn, critical) = s
+            .split_once(',')
+            .ok_or_else(|| format!("expected WARN,CRITICAL percentages, got {s:?}"))?;
+        let percent = |value: &str| {
+            value
+                .trim()
+                .trim_end_matches('%')
+                .trim_end()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .map(|v| v / 100.0)
+                .ok_or_else(|| format!("invalid percentage {value:?}"))
+        };
+        let (warn, critical) = (percent(warn)?, percent(critical)?);
+        if warn > critical {
+            return Err("the warn threshold must not be above the critical one".to_string());
+        }
+        Ok(Self::new(warn, critical))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status() {
+        assert_eq!(MEMORY_THRESHOLDS.status(0.5), Status::Normal);
+        assert_eq!(MEMORY_THRESHOLDS.status(0.8), Status::Warn);
+        assert_eq!(MEMORY_THRESHOLDS.status(0.95), Status::Critical);
+        assert_eq!(MEMORY_THRESHOLDS.status(f64::NAN), Status::Normal);
+        assert_eq!(MEMORY_THRESHOLDS.paint(0.5, "text"), "text");
+        assert_ne!(MEMORY_THRESHOLDS.paint(0.85, "text"), "text");
+    }
+
+    #[test]
+    fn check() {
+        let check = Check::new("disk /", 0.962, DISK_THRESHOLDS);
+        assert_eq!(check.status().code(), 2);
+        assert_eq!(
+            check.to_string(),
+            "CRITICAL disk /: 96.2 % (warn 90 %, critical 95 %)"
+        );
+        assert_eq!(Check::new("load", 0.1, LOAD_THRESHOLDS).status().code(), 0);
+        assert_eq!(
+            Check::new("memory", 0.5, "29,92.5".parse().unwrap()).to_string(),
+            "WARNING memory: 50.0 % (warn 29 %, critical 92.5 %)"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("80,90".parse(), Ok(Thresholds::new(0.8, 0.9)));
+        assert_eq!(" 70 %, 100%".parse(), Ok(Thresholds::new(0.7, 1.0)));
+        assert!("90".parse::<Thresholds>().is_err());
+        assert!("90,80".parse::<Thresholds>().is_err());
+        assert!("a,b".parse::<Thresholds>().is_err());
+    }
+}
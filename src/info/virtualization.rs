@@ -0,0 +1,222 @@
+use std::{fs::read_to_string, path::Path};
+
+use owo_colors::{DynColors, OwoColorize};
+
+#[cfg(feature = "sysinfo")]
+use sysinfo::System;
+
+use super::*;
+
+/// DMI vendor or product substrings of hypervisors, compared case insensitively
+const HYPERVISORS: [(&str, &str); 11] = [
+    ("kvm", "KVM"),
+    ("qemu", "QEMU"),
+    ("vmware", "VMware"),
+    ("virtualbox", "VirtualBox"),
+    ("innotek", "VirtualBox"),
+    ("xen", "Xen"),
+    ("amazon ec2", "Amazon EC2"),
+    ("google compute engine", "Google Compute Engine"),
+    ("parallels", "Parallels"),
+    ("bhyve", "bhyve"),
+    ("virtual machine", "Hyper-V"),
+];
+
+/// Cgroup path fragments of container runtimes
+const CGROUPS: [(&str, &str); 5] = [
+    ("/libpod-", "podman"),
+    ("/docker", "docker"),
+    ("kubepods", "kubernetes"),
+    ("/lxc", "lxc"),
+    ("/machine.slice/", "systemd-nspawn"),
+];
+
+/// Container runtime and hypervisor the system runs in, if any
+#[derive(Clone, Debug, Default, PartialEq, Eq, Render)]
+pub struct VirtualizationInfo {
+    pub virtualization: Option<String>,
+}
+
+impl VirtualizationInfo {
+    /// Detect containers and hypervisors from files under the given root, and the `container`
+    /// variable set by some runtimes
+    pub fn detect(root: &Path, container_env: Option<&str>) -> Self {
+        let parts: Vec<String> = [
+            container(root, container_env).map(|c| format!("{c} container")),
+            hypervisor(root),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        Self {
+            virtualization: (!parts.is_empty()).then(|| parts.join(" on ")),
+        }
+    }
+}
+
+fn read(root: &Path, path: &str) -> Option<String> {
+    let contents = read_to_string(root.join(path)).ok()?;
+    let contents = contents.trim();
+    (!contents.is_empty()).then(|| contents.to_string())
+}
+
+/// Name of the container runtime
+fn container(root: &Path, container_env: Option<&str>) -> Option<String> {
+    if let Some(name) = container_env
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .or_else(|| read(root, "run/systemd/container"))
+    {
+        return Some(name);
+    }
+    if root.join("run/.containerenv").exists() {
+        return Some("podman".to_string());
+    }
+    if root.join(".dockerenv").exists() {
+        return Some("docker".to_string());
+    }
+
+    let cgroup = read(root, "proc/1/cgroup")?;
+    CGROUPS
+        .iter()
+        .find(|(fragment, _)| cgroup.contains(fragment))
+        .map(|(_, name)| name.to_string())
+}
+
+/// Name of the hypervisor, e.g. `KVM guest`
+fn hypervisor(root: &Path) -> Option<String> {
+    // wsl 2 runs on hyper-v, but is better known by its own name
+    let osrelease = read(root, "proc/sys/kernel/osrelease").unwrap_or_default();
+    if osrelease.to_lowercase().contains("microsoft") {
+        return Some("WSL".to_string());
+    }
+
+    if let Some(kind) = read(root, "sys/hypervisor/type") {
+        return Some(format!("{} guest", kind.replace("xen", "Xen")));
+    }
+
+    let dmi = ["sys_vendor", "product_name", "bios_vendor"]
+        .iter()
+        .filter_map(|file| read(root, &format!("sys/devices/virtual/dmi/id/{file}")))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if let Some((_, name)) = HYPERVISORS.iter().find(|(needle, _)| dmi.contains(needle)) {
+        return Some(format!("{name} guest"));
+    }
+
+    // guests without DMI, like firecracker or cloud hypervisor
+    let clocksource = read(
+        root,
+        "sys/devices/system/clocksource/clocksource0/current_clocksource",
+    );
+    if clocksource.as_deref() == Some("kvm-clock") {
+        return Some("KVM guest".to_string());
+    }
+    let cpuinfo = read(root, "proc/cpuinfo").unwrap_or_default();
+    cpuinfo
+        .lines()
+        .filter_map(|l| l.strip_prefix("flags"))
+        .any(|flags| flags.split_whitespace().any(|f| f == "hypervisor"))
+        .then(|| "virtual machine".to_string())
+}
+
+#[cfg(feature = "sysinfo")]
+impl Module for VirtualizationInfo {
+    const ID: &'static str = "virtualization";
+
+    fn from_system(_: &System) -> Self {
+        Self::detect(Path::new("/"), std::env::var("container").ok().as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::fixture::Fixture, *};
+
+    fn detect(files: &[(&str, &str)], container_env: Option<&str>) -> Option<String> {
+        VirtualizationInfo::detect(&Fixture::new(files), container_env).virtualization
+    }
+
+    #[test]
+    fn containers() {
+        assert_eq!(
+            detect(&[("run/.containerenv", "")], None).as_deref(),
+            Some("podman container")
+        );
+        assert_eq!(
+            detect(&[(".dockerenv", "")], None).as_deref(),
+            Some("docker container")
+        );
+        assert_eq!(
+            detect(
+                &[("proc/1/cgroup", "0::/kubepods/besteffort/pod1234\n")],
+                None
+            )
+            .as_deref(),
+            Some("kubernetes container")
+        );
+        assert_eq!(
+            detect(&[(".dockerenv", "")], Some("lxc")).as_deref(),
+            Some("lxc container")
+        );
+    }
+
+    #[test]
+    fn hypervisors() {
+        assert_eq!(
+            detect(
+                &[
+                    ("sys/devices/virtual/dmi/id/sys_vendor", "QEMU\n"),
+                    (
+                        "sys/devices/virtual/dmi/id/product_name",
+                        "Standard PC (Q35 + ICH9, 2009)\n"
+                    ),
+                ],
+                None
+            )
+            .as_deref(),
+            Some("QEMU guest")
+        );
+        assert_eq!(
+            detect(
+                &[(
+                    "proc/sys/kernel/osrelease",
+                    "5.15.90.1-microsoft-standard-WSL2\n"
+                )],
+                None
+            )
+            .as_deref(),
+            Some("WSL")
+        );
+        assert_eq!(
+            detect(
+                &[
+                    (".dockerenv", ""),
+                    (
+                        "proc/cpuinfo",
+                        "processor\t: 0\nflags\t\t: fpu hypervisor\n"
+                    ),
+                ],
+                None
+            )
+            .as_deref(),
+            Some("docker container on virtual machine")
+        );
+    }
+
+    #[test]
+    fn bare_metal() {
+        assert_eq!(
+            detect(
+                &[
+                    ("proc/1/cgroup", "0::/init.scope\n"),
+                    ("proc/cpuinfo", "processor\t: 0\nflags\t\t: fpu vmx\n"),
+                    ("sys/devices/virtual/dmi/id/sys_vendor", "Framework\n"),
+                ],
+                None
+            ),
+            None
+        );
+    }
+}
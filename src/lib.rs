@@ -19,9 +19,19 @@ use owo_colors::{AnsiColors, DynColors};
 
 pub use punfetch_derive::Render;
 
-pub use crate::distros::Distro;
-
+pub use crate::{
+    cache::Cache,
+    collector::{Collected, Collector},
+    distros::Distro,
+    timings::Timings,
+};
+
+/// On-disk cache for slow modules
+pub mod cache;
+/// Concurrent module collection with a per-module time budget
+pub mod collector;
 mod distros;
+mod timings;
 
 /// A collection of structs implementing [`Render`]
 pub mod info;
@@ -37,6 +47,12 @@ impl Render for Vec<String> {
     }
 }
 
+impl<R: Render + ?Sized> Render for Box<R> {
+    fn render(&self, color: DynColors) -> Vec<String> {
+        (**self).render(color)
+    }
+}
+
 impl Render for String {
     fn render(&self, _: DynColors) -> Vec<String> {
         vec![self.to_owned()]
@@ -87,9 +103,24 @@ impl<'a> Printer<'a> {
         self.info.push(Box::new(info));
     }
 
+    /// Columns taken by the logo and its padding, left of the info lines. `None` for images,
+    /// which are scaled to the height of the info lines.
+    pub fn logo_width(&self) -> Option<usize> {
+        if self.image.is_some() && get_best_backend().is_some() {
+            None
+        } else {
+            Some(self.ascii.as_ref().map_or(0, |art| art.width() + 4))
+        }
+    }
+
     /// Render the ascii art and print it to stdout
     #[inline]
     pub fn render(&mut self) {
+        print!("{}", self.render_to_string())
+    }
+
+    /// Render the ascii art or image alongside the info lines
+    pub fn render_to_string(&mut self) -> String {
         let mut buf = String::new();
         let color = self.color;
         let lines = self.info.iter().flat_map(|i| i.render(color)).collect();
@@ -124,6 +155,6 @@ impl<'a> Printer<'a> {
             writeln!(buf).expect("failed to write to buffer");
         }
 
-        print!("{buf}")
+        buf
     }
 }
@@ -0,0 +1,58 @@
+use std::{
+    cmp::Reverse,
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+/// Labelled durations measured during a fetch, displayed as a table sorted slowest first
+#[derive(Default)]
+pub struct Timings(pub Vec<(String, Duration)>);
+
+impl Timings {
+    /// Record a measured duration
+    #[inline]
+    pub fn push<S: Into<String>>(&mut self, label: S, duration: Duration) {
+        self.0.push((label.into(), duration));
+    }
+
+    /// Run a closure and record how long it took
+    #[inline]
+    pub fn time<S: Into<String>, T, F: FnOnce() -> T>(&mut self, label: S, f: F) -> T {
+        let start = Instant::now();
+        let res = f();
+        self.push(label, start.elapsed());
+        res
+    }
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sorted: Vec<_> = self.0.iter().collect();
+        sorted.sort_by_key(|(_, duration)| Reverse(*duration));
+        for (label, duration) in sorted {
+            writeln!(f, "{:>10.3} ms  {label}", duration.as_secs_f64() * 1000.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted_table() {
+        let mut timings = Timings::default();
+        timings.push("fast", Duration::from_micros(1500));
+        timings.push("slow", Duration::from_millis(20));
+        assert_eq!(timings.time("closure", || 42), 42);
+
+        let table = timings.to_string();
+        let labels: Vec<_> = table
+            .lines()
+            .map(|l| l.split_whitespace().last().unwrap())
+            .collect();
+        assert_eq!(labels, ["slow", "fast", "closure"]);
+        assert!(table.starts_with("    20.000 ms  slow\n"));
+    }
+}